@@ -2,90 +2,274 @@
 
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{ItemFn, parse_macro_input};
+use syn::{Attribute, FnArg, ItemFn, LitStr, Pat, parse_macro_input};
 
 /// Procedural macro for creating function tools
 ///
-/// This macro transforms an async function into a Tool implementation.
+/// This macro transforms a function into a Tool implementation.
+///
+/// Async functions are awaited on the runner's task, while sync functions are
+/// run with `tokio::task::spawn_blocking`. A function returning
+/// `Result<T, E>` with `E: Display` reports `Err` as
+/// `AgentError::ToolExecutionFailed` instead of serializing it as output.
+///
+/// The JSON schema of each parameter is derived from its Rust type, so every
+/// parameter type must implement `schemars::JsonSchema` and
+/// `serde::Deserialize`. `Option<T>` parameters are optional: their schema is
+/// nullable and a missing or `null` argument becomes `None`. Strict function
+/// calling requires every property to be listed as required, so optional
+/// parameters are expressed as nullable rather than left out of `required`.
+///
+/// # Attributes
+///
+/// - `name = "..."` overrides the tool name (defaults to the function name)
+/// - `description = "..."` overrides the description (defaults to the doc comment)
+/// - `timeout_ms = ...` sets the tool's timeout (defaults to `RunConfig::tool_timeout`)
+///
+/// A tool can receive the run context by declaring `ctx: &RunContext<T>` as its
+/// first parameter, where `T` is the application context set with
+/// `RunConfig::with_context`. Use `&RunContext` to receive the untyped context.
+///
+/// Parameters can be documented with doc comments or with
+/// `#[arg(description = "...")]`, and exposed under a different name with
+/// `#[arg(rename = "...")]`.
 ///
 /// # Example
 ///
 /// ```rust,ignore
 /// use openai_agents::function_tool;
 ///
-/// #[function_tool]
-/// async fn get_weather(city: String) -> String {
+/// /// Get the current weather for a city.
+/// #[function_tool(name = "weather")]
+/// async fn get_weather(
+///     /// The city to look up
+///     city: String,
+///     #[arg(rename = "unit", description = "Either `C` or `F`")] temperature_unit: Option<String>,
+/// ) -> String {
 ///     format!("The weather in {} is sunny", city)
 /// }
 /// ```
 #[proc_macro_attribute]
-pub fn function_tool(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(item as ItemFn);
+pub fn function_tool(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut tool_args = ToolArgs::default();
+    let parser = syn::meta::parser(|meta| tool_args.parse(meta));
+    parse_macro_input!(attr with parser);
 
-    let fn_name = &input.sig.ident;
-    let fn_vis = &input.vis;
+    let mut input = parse_macro_input!(item as ItemFn);
 
-    // Extract description from doc comments
-    let mut description = String::new();
-    for attr in &input.attrs {
-        if attr.path().is_ident("doc") {
-            if let syn::Meta::NameValue(syn::MetaNameValue {
-                value:
-                    syn::Expr::Lit(syn::ExprLit {
-                        lit: syn::Lit::Str(s),
-                        ..
-                    }),
-                ..
-            }) = &attr.meta
-            {
-                let val = s.value().trim().to_string();
-                if !description.is_empty() {
-                    description.push(' ');
-                }
-                description.push_str(&val);
-            }
+    match expand(tool_args, &mut input) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(err) => {
+            let err = err.to_compile_error();
+            TokenStream::from(quote! {
+                #err
+                #input
+            })
         }
     }
-    if description.is_empty() {
-        description = fn_name.to_string();
+}
+
+/// Arguments of the `#[function_tool(...)]` attribute
+#[derive(Default)]
+struct ToolArgs {
+    name: Option<String>,
+    description: Option<String>,
+    timeout_ms: Option<u64>,
+}
+
+impl ToolArgs {
+    fn parse(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else if meta.path.is_ident("description") {
+            self.description = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else if meta.path.is_ident("timeout_ms") {
+            self.timeout_ms = Some(meta.value()?.parse::<syn::LitInt>()?.base10_parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported function_tool argument"))
+        }
+    }
+}
+
+/// Options of a `#[arg(...)]` parameter attribute
+#[derive(Default)]
+struct ParamArgs {
+    description: Option<String>,
+    rename: Option<String>,
+}
+
+impl ParamArgs {
+    fn parse(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("description") {
+            self.description = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else if meta.path.is_ident("rename") {
+            self.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else {
+            Err(meta.error("unsupported arg option, expected `description` or `rename`"))
+        }
     }
+}
+
+fn expand(tool_args: ToolArgs, input: &mut ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let fn_name = input.sig.ident.clone();
+    let fn_vis = input.vis.clone();
+
+    let tool_name = tool_args.name.unwrap_or_else(|| fn_name.to_string());
+    let description = tool_args
+        .description
+        .or_else(|| doc_comment(&input.attrs))
+        .unwrap_or_else(|| fn_name.to_string());
 
     // Extract function parameters for schema and execution
     let mut properties = Vec::new();
     let mut required = Vec::new();
     let mut param_names = Vec::new();
     let mut param_deserialization = Vec::new();
+    let mut errors: Option<syn::Error> = None;
+
+    for (index, arg) in input.sig.inputs.iter_mut().enumerate() {
+        let pat_type = match arg {
+            FnArg::Typed(pat_type) => pat_type,
+            FnArg::Receiver(receiver) => {
+                combine(
+                    &mut errors,
+                    syn::Error::new_spanned(
+                        receiver,
+                        "#[function_tool] cannot be used on methods with a `self` receiver",
+                    ),
+                );
+                continue;
+            }
+        };
 
-    for arg in &input.sig.inputs {
-        if let syn::FnArg::Typed(pat_type) = arg {
-            if let syn::Pat::Ident(pat_ident) = &*pat_type.pat {
-                let name = &pat_ident.ident;
-                let name_str = name.to_string();
-
-                // For now, assume everything is a string/deserializable
-                properties.push(quote! {
-                    #name_str: {
-                        "type": "string"
-                    }
-                });
-                required.push(quote! { #name_str });
-                param_names.push(name);
-
-                param_deserialization.push(quote! {
-                    let #name = serde_json::from_value(args[#name_str].clone())
-                        .map_err(|e| openai_agents::AgentError::tool_failed(stringify!(#fn_name), e.to_string()))?;
-                });
+        // Parameter attributes are consumed here; they are not valid on the emitted function
+        let param_args = param_args(&pat_type.attrs);
+        let param_doc = doc_comment(&pat_type.attrs);
+        pat_type
+            .attrs
+            .retain(|attr| !attr.path().is_ident("doc") && !attr.path().is_ident("arg"));
+        let param_args = match param_args {
+            Ok(param_args) => param_args,
+            Err(err) => {
+                combine(&mut errors, err);
+                continue;
             }
+        };
+
+        let name = match &*pat_type.pat {
+            Pat::Ident(pat_ident) => pat_ident.ident.clone(),
+            other => {
+                combine(
+                    &mut errors,
+                    syn::Error::new_spanned(
+                        other,
+                        "#[function_tool] parameters must be plain identifiers, destructuring is not supported",
+                    ),
+                );
+                continue;
+            }
+        };
+        let ty = &pat_type.ty;
+
+        // A leading `ctx: &RunContext<T>` receives the run context instead of an argument
+        if let Some(context_type) = run_context_param(ty) {
+            if index != 0 {
+                combine(
+                    &mut errors,
+                    syn::Error::new_spanned(ty, "the run context must be the first parameter"),
+                );
+                continue;
+            }
+            param_deserialization.push(match context_type {
+                Some(context_type) => quote! {
+                    let #name = __ctx.downcast::<#context_type>().ok_or_else(|| {
+                        openai_agents::AgentError::ConfigError(format!(
+                            "Tool '{}' expects a run context of type {}",
+                            #tool_name,
+                            stringify!(#context_type)
+                        ))
+                    })?;
+                },
+                None => quote! {
+                    let #name = __ctx.clone();
+                },
+            });
+            param_names.push(quote! { &#name });
+            continue;
         }
+
+        let name_str = param_args.rename.unwrap_or_else(|| name.to_string());
+
+        // The schema is derived from the parameter's Rust type
+        let describe = param_args.description.or(param_doc).map(|description| {
+            quote! {
+                if let Some(obj) = schema.as_object_mut() {
+                    obj.insert("description".to_string(), serde_json::Value::from(#description));
+                }
+            }
+        });
+        properties.push(quote! {
+            let mut schema = openai_agents::schema::strict_schema_for::<#ty>();
+            #describe
+            properties.insert(#name_str.to_string(), schema);
+        });
+        required.push(quote! { #name_str });
+
+        // A missing argument is treated as null so `Option<T>` parameters become `None`
+        param_deserialization.push(quote! {
+            let #name: #ty = serde_json::from_value(
+                args.get(#name_str).cloned().unwrap_or(serde_json::Value::Null),
+            )
+            .map_err(|e| openai_agents::AgentError::tool_failed(#tool_name, e.to_string()))?;
+        });
+        param_names.push(quote! { #name });
+    }
+
+    if let Some(errors) = errors {
+        return Err(errors);
     }
 
+    // Sync functions run on the blocking pool so they can't stall the runtime
+    let call = if input.sig.asyncness.is_some() {
+        quote! {
+            let result = #fn_name(#(#param_names),*).await;
+        }
+    } else {
+        quote! {
+            let result = tokio::task::spawn_blocking(move || #fn_name(#(#param_names),*))
+                .await
+                .map_err(|e| openai_agents::AgentError::tool_failed(#tool_name, e.to_string()))?;
+        }
+    };
+
+    // An `Err` is a tool failure, not output to serialize
+    let unwrap_result = returns_result(&input.sig.output).then(|| {
+        quote! {
+            let result = result
+                .map_err(|e| openai_agents::AgentError::tool_failed(#tool_name, e.to_string()))?;
+        }
+    });
+
+    let timeout = tool_args.timeout_ms.map(|timeout_ms| {
+        quote! {
+            fn timeout(&self) -> Option<std::time::Duration> {
+                Some(std::time::Duration::from_millis(#timeout_ms))
+            }
+        }
+    });
+
     // Generate the tool struct name (uppercase version of fn name + Tool)
     let tool_struct_name = syn::Ident::new(
         &format!("{}Tool", fn_name.to_string().to_uppercase()),
         fn_name.span(),
     );
 
-    let expanded = quote! {
+    Ok(quote! {
         #input
 
         #[allow(non_camel_case_types)]
@@ -95,7 +279,7 @@ pub fn function_tool(_attr: TokenStream, item: TokenStream) -> TokenStream {
         #[async_trait::async_trait]
         impl openai_agents::Tool for #tool_struct_name {
             fn name(&self) -> &str {
-                stringify!(#fn_name)
+                #tool_name
             }
 
             fn description(&self) -> &str {
@@ -103,22 +287,102 @@ pub fn function_tool(_attr: TokenStream, item: TokenStream) -> TokenStream {
             }
 
             fn parameters_schema(&self) -> serde_json::Value {
+                let mut properties = serde_json::Map::new();
+                #(#properties)*
                 serde_json::json!({
                     "type": "object",
-                    "properties": {
-                        #(#properties),*
-                    },
-                    "required": [#(#required),*]
+                    "properties": properties,
+                    "required": [#(#required),*],
+                    "additionalProperties": false
                 })
             }
 
-            async fn execute(&self, args: serde_json::Value) -> openai_agents::Result<serde_json::Value> {
+            async fn execute(&self, __ctx: &openai_agents::RunContext, args: serde_json::Value) -> openai_agents::Result<serde_json::Value> {
                 #(#param_deserialization)*
-                let result = #fn_name(#(#param_names),*).await;
-                Ok(serde_json::to_value(result).map_err(|e| openai_agents::AgentError::tool_failed(stringify!(#fn_name), e.to_string()))?)
+                #call
+                #unwrap_result
+                Ok(serde_json::to_value(result).map_err(|e| openai_agents::AgentError::tool_failed(#tool_name, e.to_string()))?)
             }
+
+            #timeout
         }
+    })
+}
+
+/// Join the lines of the doc comments in `attrs`, if any
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let mut description = String::new();
+    for attr in attrs {
+        if attr.path().is_ident("doc") {
+            if let syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(s),
+                        ..
+                    }),
+                ..
+            }) = &attr.meta
+            {
+                let val = s.value().trim().to_string();
+                if !description.is_empty() {
+                    description.push(' ');
+                }
+                description.push_str(&val);
+            }
+        }
+    }
+    (!description.is_empty()).then_some(description)
+}
+
+/// If `ty` is `&RunContext` or `&RunContext<T>`, the context type `T` if one is given
+fn run_context_param(ty: &syn::Type) -> Option<Option<syn::Type>> {
+    let syn::Type::Reference(reference) = ty else {
+        return None;
     };
+    let syn::Type::Path(type_path) = &*reference.elem else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "RunContext" {
+        return None;
+    }
+    let context_type = match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+            syn::GenericArgument::Type(ty) => Some(ty.clone()),
+            _ => None,
+        }),
+        _ => None,
+    };
+    Some(context_type)
+}
 
-    TokenStream::from(expanded)
+/// Whether the function returns a `Result<T, E>` (including `Result` aliases)
+fn returns_result(output: &syn::ReturnType) -> bool {
+    match output {
+        syn::ReturnType::Type(_, ty) => match &**ty {
+            syn::Type::Path(type_path) => type_path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Result"),
+            _ => false,
+        },
+        syn::ReturnType::Default => false,
+    }
+}
+
+/// Parse all `#[arg(...)]` attributes of a parameter
+fn param_args(attrs: &[Attribute]) -> syn::Result<ParamArgs> {
+    let mut args = ParamArgs::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("arg")) {
+        attr.parse_nested_meta(|meta| args.parse(meta))?;
+    }
+    Ok(args)
+}
+
+fn combine(errors: &mut Option<syn::Error>, err: syn::Error) {
+    match errors {
+        Some(errors) => errors.combine(err),
+        None => *errors = Some(err),
+    }
 }
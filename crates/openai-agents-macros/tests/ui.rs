@@ -0,0 +1,7 @@
+//! Compile-fail tests for the errors reported by `#[function_tool]`
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,9 @@
+use openai_agents_macros::function_tool;
+
+/// Add a pair of numbers
+#[function_tool]
+fn add((a, b): (i64, i64)) -> i64 {
+    a + b
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use openai_agents_macros::function_tool;
+
+struct Calculator;
+
+impl Calculator {
+    /// Add two numbers
+    #[function_tool]
+    fn add(&self, a: i64, b: i64) -> i64 {
+        a + b
+    }
+}
+
+fn main() {}
@@ -1,6 +1,6 @@
 // Handoffs example - multi-agent workflow
 
-use openai_agents::{Agent, Handoff, Runner};
+use openai_agents::{Agent, Handoff, Message, Runner};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -32,6 +32,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let result = Runner::run(&triage_agent, "Hola, ¿cómo estás?").await?;
     println!("Response: {}\n", result.final_output());
 
+    // Continue the conversation with the agent that answered
+    println!("Continuing with the Spanish agent:");
+    let mut input = result.to_input_list();
+    input.push(Message::user("¿Qué tiempo hace hoy?"));
+    let spanish_agent = result.last_agent().unwrap();
+    let result = Runner::run(spanish_agent, input).await?;
+    println!("Response: {}\n", result.final_output());
+
     // Test with English input
     println!("Testing with English input:");
     let result = Runner::run(&triage_agent, "Hello, how are you?").await?;
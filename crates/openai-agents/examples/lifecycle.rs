@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 use dotenvy::dotenv;
-use openai_agents::{Agent, AgentHooks, RunConfig, RunHooks, Runner, function_tool};
+use openai_agents::{Agent, AgentHooks, RunConfig, RunContext, RunHooks, Runner, function_tool};
 use std::sync::Arc;
 
 #[function_tool]
@@ -12,18 +12,24 @@ struct LoggerHooks;
 
 #[async_trait]
 impl AgentHooks for LoggerHooks {
-    async fn on_start(&self, agent: &Agent) -> openai_agents::error::Result<()> {
+    async fn on_start(&self, _ctx: &RunContext, agent: &Agent) -> openai_agents::error::Result<()> {
         println!("🚀 Agent '{}' starting...", agent.name);
         Ok(())
     }
 
-    async fn on_end(&self, agent: &Agent, output: &str) -> openai_agents::error::Result<()> {
+    async fn on_end(
+        &self,
+        _ctx: &RunContext,
+        agent: &Agent,
+        output: &str,
+    ) -> openai_agents::error::Result<()> {
         println!("🏁 Agent '{}' finished with output: {}", agent.name, output);
         Ok(())
     }
 
     async fn on_tool_start(
         &self,
+        _ctx: &RunContext,
         agent: &Agent,
         tool_name: &str,
         arguments: &serde_json::Value,
@@ -40,12 +46,21 @@ struct GlobalHooks;
 
 #[async_trait]
 impl RunHooks for GlobalHooks {
-    async fn on_agent_start(&self, agent: &Agent) -> openai_agents::error::Result<()> {
+    async fn on_agent_start(
+        &self,
+        _ctx: &RunContext,
+        agent: &Agent,
+    ) -> openai_agents::error::Result<()> {
         println!("🌐 Global: Agent '{}' is now active", agent.name);
         Ok(())
     }
 
-    async fn on_handoff(&self, from: &Agent, to: &Agent) -> openai_agents::error::Result<()> {
+    async fn on_handoff(
+        &self,
+        _ctx: &RunContext,
+        from: &Agent,
+        to: &Agent,
+    ) -> openai_agents::error::Result<()> {
         println!("🤝 Global: Handoff from '{}' to '{}'", from.name, to.name);
         Ok(())
     }
@@ -53,6 +53,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     agent_event.new_agent.name
                 );
             }
+            StreamEvent::GuardrailTripped(tripped) => {
+                println!(
+                    "\n🛑 Guardrail '{}' tripped: {}",
+                    tripped.guardrail, tripped.reason
+                );
+            }
         }
     }
 
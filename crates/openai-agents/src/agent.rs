@@ -5,10 +5,11 @@ use serde::de::DeserializeOwned;
 use std::sync::Arc;
 
 use crate::{
-    guardrail::{InputGuardrail, OutputGuardrail},
+    guardrail::{InputGuardrail, OutputGuardrail, ToolInputGuardrail, ToolOutputGuardrail},
     handoff::Handoff,
     lifecycle::AgentHooks,
-    tool::Tool,
+    schema,
+    tool::{Tool, ToolErrorPolicy},
 };
 
 /// An agent that can process inputs and produce outputs
@@ -35,6 +36,12 @@ pub struct Agent {
     /// Output guardrails
     pub output_guardrails: Vec<Arc<dyn OutputGuardrail>>,
 
+    /// Guardrails checking the arguments of every tool call of this agent
+    pub tool_input_guardrails: Vec<Arc<dyn ToolInputGuardrail>>,
+
+    /// Guardrails checking the output of every tool call of this agent
+    pub tool_output_guardrails: Vec<Arc<dyn ToolOutputGuardrail>>,
+
     /// Whether to allow parallel tool calls
     pub parallel_tool_calls: bool,
 
@@ -46,6 +53,32 @@ pub struct Agent {
 
     /// Name of the output schema
     pub output_name: Option<String>,
+
+    /// How failing tool calls are handled (overrides `RunConfig::tool_error_policy`)
+    pub tool_error_policy: Option<ToolErrorPolicy>,
+
+    /// Limits on what the agent may spend over a run
+    pub limits: AgentLimits,
+}
+
+/// Limits on the turns, tokens and tool calls of an agent over a run
+///
+/// Each limit covers everything the agent does in a run, also across
+/// handoffs back to it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AgentLimits {
+    /// Maximum number of model calls
+    pub max_turns: Option<usize>,
+    /// Maximum number of completion tokens
+    pub max_output_tokens: Option<u64>,
+    /// Maximum number of prompt and completion tokens
+    pub max_total_tokens: Option<u64>,
+    /// Maximum number of tool calls, not counting handoffs
+    pub max_tool_calls: Option<usize>,
+    /// Whether running out of turns or tool calls (including the run's
+    /// `max_turns`) gives the agent a last turn without tools to produce a
+    /// best-effort answer, instead of failing the run
+    pub final_answer_on_limit: bool,
 }
 
 impl Agent {
@@ -64,10 +97,14 @@ pub struct AgentBuilder {
     handoffs: Vec<Handoff>,
     input_guardrails: Vec<Arc<dyn InputGuardrail>>,
     output_guardrails: Vec<Arc<dyn OutputGuardrail>>,
+    tool_input_guardrails: Vec<Arc<dyn ToolInputGuardrail>>,
+    tool_output_guardrails: Vec<Arc<dyn ToolOutputGuardrail>>,
     parallel_tool_calls: bool,
     hooks: Vec<Arc<dyn AgentHooks>>,
     output_schema: Option<serde_json::Value>,
     output_name: Option<String>,
+    tool_error_policy: Option<ToolErrorPolicy>,
+    limits: AgentLimits,
 }
 
 impl AgentBuilder {
@@ -81,10 +118,14 @@ impl AgentBuilder {
             handoffs: Vec::new(),
             input_guardrails: Vec::new(),
             output_guardrails: Vec::new(),
+            tool_input_guardrails: Vec::new(),
+            tool_output_guardrails: Vec::new(),
             parallel_tool_calls: true,
             hooks: Vec::new(),
             output_schema: None,
             output_name: None,
+            tool_error_policy: None,
+            limits: AgentLimits::default(),
         }
     }
 
@@ -124,12 +165,61 @@ impl AgentBuilder {
         self
     }
 
+    /// Add a guardrail checking the arguments of every tool call
+    pub fn tool_input_guardrail(mut self, guardrail: impl ToolInputGuardrail + 'static) -> Self {
+        self.tool_input_guardrails.push(Arc::new(guardrail));
+        self
+    }
+
+    /// Add a guardrail checking the output of every tool call
+    pub fn tool_output_guardrail(mut self, guardrail: impl ToolOutputGuardrail + 'static) -> Self {
+        self.tool_output_guardrails.push(Arc::new(guardrail));
+        self
+    }
+
     /// Set whether to allow parallel tool calls
     pub fn parallel_tool_calls(mut self, parallel: bool) -> Self {
         self.parallel_tool_calls = parallel;
         self
     }
 
+    /// Set how failing tool calls of this agent are handled
+    pub fn tool_error_policy(mut self, policy: ToolErrorPolicy) -> Self {
+        self.tool_error_policy = Some(policy);
+        self
+    }
+
+    /// Limit the number of model calls of the agent
+    pub fn max_turns(mut self, max_turns: usize) -> Self {
+        self.limits.max_turns = Some(max_turns);
+        self
+    }
+
+    /// Limit the number of completion tokens of the agent
+    pub fn max_output_tokens(mut self, max_output_tokens: u64) -> Self {
+        self.limits.max_output_tokens = Some(max_output_tokens);
+        self
+    }
+
+    /// Limit the number of prompt and completion tokens of the agent
+    pub fn max_total_tokens(mut self, max_total_tokens: u64) -> Self {
+        self.limits.max_total_tokens = Some(max_total_tokens);
+        self
+    }
+
+    /// Limit the number of tool calls of the agent
+    pub fn max_tool_calls(mut self, max_tool_calls: usize) -> Self {
+        self.limits.max_tool_calls = Some(max_tool_calls);
+        self
+    }
+
+    /// Set whether running out of turns or tool calls ends with a best-effort
+    /// answer instead of an error
+    pub fn final_answer_on_limit(mut self, final_answer: bool) -> Self {
+        self.limits.final_answer_on_limit = final_answer;
+        self
+    }
+
     /// Add a lifecycle hook to the agent
     pub fn hook(mut self, hook: impl AgentHooks + 'static) -> Self {
         self.hooks.push(Arc::new(hook));
@@ -145,24 +235,7 @@ impl AgentBuilder {
         let mut schema_json = serde_json::to_value(schema).unwrap_or_default();
 
         // OpenAI requirement: all objects must have additionalProperties: false
-        fn ensure_no_additional_properties(value: &mut serde_json::Value) {
-            if let Some(obj) = value.as_object_mut() {
-                if obj.contains_key("type") && obj["type"] == "object" {
-                    obj.insert(
-                        "additionalProperties".to_string(),
-                        serde_json::Value::Bool(false),
-                    );
-                }
-                for (_, v) in obj.iter_mut() {
-                    ensure_no_additional_properties(v);
-                }
-            } else if let Some(arr) = value.as_array_mut() {
-                for v in arr.iter_mut() {
-                    ensure_no_additional_properties(v);
-                }
-            }
-        }
-        ensure_no_additional_properties(&mut schema_json);
+        schema::ensure_no_additional_properties(&mut schema_json);
 
         self.output_schema = Some(schema_json);
 
@@ -188,10 +261,14 @@ impl AgentBuilder {
             handoffs: self.handoffs,
             input_guardrails: self.input_guardrails,
             output_guardrails: self.output_guardrails,
+            tool_input_guardrails: self.tool_input_guardrails,
+            tool_output_guardrails: self.tool_output_guardrails,
             parallel_tool_calls: self.parallel_tool_calls,
             hooks: self.hooks,
             output_schema: self.output_schema,
             output_name: self.output_name,
+            tool_error_policy: self.tool_error_policy,
+            limits: self.limits,
         }
     }
 }
@@ -0,0 +1,156 @@
+//! Run context shared with tools, hooks and guardrails
+//!
+//! The context carries application state (database pools, the current user,
+//! auth tokens, ...) supplied through [`RunConfig`](crate::RunConfig) along
+//! with details about the run itself, so that per-request state does not have
+//! to live in globals.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use crate::session::Session;
+use crate::usage::UsageMeter;
+
+/// Context of an agent run
+///
+/// The runner hands out the type-erased `RunContext`; use [`RunContext::get`]
+/// or [`RunContext::downcast`] to recover the typed application state.
+///
+/// # Example
+///
+/// ```rust
+/// use openai_agents::{RunConfig, RunContext};
+///
+/// struct AppState {
+///     user_id: u64,
+/// }
+///
+/// let config = RunConfig::default().with_context(AppState { user_id: 42 });
+///
+/// // Inside a tool, hook or guardrail:
+/// let ctx = RunContext::new(AppState { user_id: 42 });
+/// let state = ctx.downcast::<AppState>().unwrap();
+/// assert_eq!(state.context().user_id, 42);
+/// # let _ = config;
+/// ```
+pub struct RunContext<T: ?Sized = dyn Any + Send + Sync> {
+    context: Arc<T>,
+    agent_name: String,
+    tool_call_id: Option<String>,
+    session: Option<Arc<dyn Session>>,
+    usage: Option<UsageMeter>,
+}
+
+impl<T: ?Sized> RunContext<T> {
+    /// Get the application context
+    pub fn context(&self) -> &T {
+        &self.context
+    }
+
+    /// Get a shared handle to the application context
+    pub fn context_arc(&self) -> Arc<T> {
+        self.context.clone()
+    }
+
+    /// Name of the agent that is currently running
+    pub fn agent_name(&self) -> &str {
+        &self.agent_name
+    }
+
+    /// Id of the tool call being executed, when called from a tool
+    pub fn tool_call_id(&self) -> Option<&str> {
+        self.tool_call_id.as_deref()
+    }
+
+    /// The session of the run, if any
+    pub fn session(&self) -> Option<&Arc<dyn Session>> {
+        self.session.as_ref()
+    }
+}
+
+impl RunContext {
+    /// Create a context wrapping the given application state
+    pub fn new<T: Any + Send + Sync>(context: T) -> Self {
+        Self::from_arc(Arc::new(context))
+    }
+
+    /// Create a context from an already shared application state
+    pub fn from_arc(context: Arc<dyn Any + Send + Sync>) -> Self {
+        Self {
+            context,
+            agent_name: String::new(),
+            tool_call_id: None,
+            session: None,
+            usage: None,
+        }
+    }
+
+    /// Borrow the application context as `T`
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.context.downcast_ref::<T>()
+    }
+
+    /// Convert into a typed context, if the application context is a `T`
+    pub fn downcast<T: Any + Send + Sync>(&self) -> Option<RunContext<T>> {
+        let context = self.context.clone().downcast::<T>().ok()?;
+        Some(RunContext {
+            context,
+            agent_name: self.agent_name.clone(),
+            tool_call_id: self.tool_call_id.clone(),
+            session: self.session.clone(),
+            usage: self.usage.clone(),
+        })
+    }
+
+    /// Set the session of the run
+    pub fn with_session(mut self, session: Option<Arc<dyn Session>>) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// Set the usage that nested runs made on behalf of the run count towards
+    pub(crate) fn with_usage_meter(mut self, meter: UsageMeter) -> Self {
+        self.usage = Some(meter);
+        self
+    }
+
+    /// The usage of the run, if the context belongs to one
+    pub(crate) fn usage_meter(&self) -> Option<&UsageMeter> {
+        self.usage.as_ref()
+    }
+
+    /// Copy of this context for the given agent
+    pub fn for_agent(&self, agent_name: impl Into<String>) -> Self {
+        Self {
+            agent_name: agent_name.into(),
+            tool_call_id: None,
+            ..self.clone()
+        }
+    }
+
+    /// Copy of this context for the given tool call
+    pub fn for_tool_call(&self, tool_call_id: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: Some(tool_call_id.into()),
+            ..self.clone()
+        }
+    }
+}
+
+impl Default for RunContext {
+    fn default() -> Self {
+        Self::new(())
+    }
+}
+
+impl<T: ?Sized> Clone for RunContext<T> {
+    fn clone(&self) -> Self {
+        Self {
+            context: self.context.clone(),
+            agent_name: self.agent_name.clone(),
+            tool_call_id: self.tool_call_id.clone(),
+            session: self.session.clone(),
+            usage: self.usage.clone(),
+        }
+    }
+}
@@ -10,20 +10,70 @@ pub enum AgentError {
     MaxTurnsExceeded(usize),
 
     /// Input guardrail was triggered
-    #[error("Input guardrail triggered: {0}")]
-    InputGuardrailTriggered(String),
+    #[error("Input guardrail '{guardrail}' triggered: {reason}")]
+    InputGuardrailTriggered {
+        guardrail: String,
+        reason: String,
+        category: Option<String>,
+        output_info: Option<Box<serde_json::Value>>,
+    },
 
     /// Output guardrail was triggered
-    #[error("Output guardrail triggered: {0}")]
-    OutputGuardrailTriggered(String),
+    #[error("Output guardrail '{guardrail}' triggered: {reason}")]
+    OutputGuardrailTriggered {
+        guardrail: String,
+        reason: String,
+        category: Option<String>,
+        output_info: Option<Box<serde_json::Value>>,
+    },
 
     /// Tool input guardrail was triggered
-    #[error("Tool input guardrail triggered: {0}")]
-    ToolInputGuardrailTriggered(String),
+    #[error("Tool input guardrail '{guardrail}' triggered for {tool_name}: {reason}")]
+    ToolInputGuardrailTriggered {
+        guardrail: String,
+        tool_name: String,
+        reason: String,
+        category: Option<String>,
+        output_info: Option<Box<serde_json::Value>>,
+    },
 
     /// Tool output guardrail was triggered
-    #[error("Tool output guardrail triggered: {0}")]
-    ToolOutputGuardrailTriggered(String),
+    #[error("Tool output guardrail '{guardrail}' triggered for {tool_name}: {reason}")]
+    ToolOutputGuardrailTriggered {
+        guardrail: String,
+        tool_name: String,
+        reason: String,
+        category: Option<String>,
+        output_info: Option<Box<serde_json::Value>>,
+    },
+
+    /// An agent used up its turns
+    #[error("Agent '{agent}' exceeded its limit of {limit} turns")]
+    AgentMaxTurnsExceeded { agent: String, limit: usize },
+
+    /// An agent used up its output tokens
+    #[error("Agent '{agent}' used {used} output tokens, exceeding its limit of {limit}")]
+    MaxOutputTokensExceeded {
+        agent: String,
+        limit: u64,
+        used: u64,
+    },
+
+    /// An agent used up its total tokens
+    #[error("Agent '{agent}' used {used} tokens, exceeding its limit of {limit}")]
+    MaxTotalTokensExceeded {
+        agent: String,
+        limit: u64,
+        used: u64,
+    },
+
+    /// An agent requested more tool calls than it may make
+    #[error("Agent '{agent}' exceeded its limit of {limit} tool calls")]
+    MaxToolCallsExceeded { agent: String, limit: usize },
+
+    /// Estimated spend of the run exceeded its budget
+    #[error("Budget exceeded: spent ${spent:.4} of ${limit:.4}")]
+    BudgetExceeded { limit: f64, spent: f64 },
 
     /// Tool execution failed
     #[error("Tool execution failed: {tool_name}: {reason}")]
@@ -1,8 +1,17 @@
 //! Guardrail system for input and output validation
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use crate::error::Result;
+use crate::agent::Agent;
+use crate::context::RunContext;
+use crate::error::{AgentError, Result};
+use crate::models::ModelProvider;
+use crate::runner::{RunConfig, Runner};
 
 /// Result of a guardrail check
 #[derive(Debug, Clone)]
@@ -15,30 +24,389 @@ pub enum GuardrailResult {
     Modify { new_content: String },
 }
 
+impl GuardrailResult {
+    /// Attach a category to the result
+    pub fn with_category(self, category: impl Into<String>) -> GuardrailOutput {
+        GuardrailOutput::from(self).with_category(category)
+    }
+
+    /// Attach machine-readable details to the result
+    pub fn with_output_info(self, output_info: Value) -> GuardrailOutput {
+        GuardrailOutput::from(self).with_output_info(output_info)
+    }
+}
+
+/// Output of a guardrail check: its result plus metadata for auditing
+///
+/// Plain results convert into an output without metadata, so a guardrail can
+/// return `Ok(GuardrailResult::Allow.into())`.
+#[derive(Debug, Clone)]
+pub struct GuardrailOutput {
+    /// What to do with the checked content
+    pub result: GuardrailResult,
+    /// Category of the finding (e.g. `"pii"` or `"off_topic"`)
+    pub category: Option<String>,
+    /// Machine-readable details of the evaluation
+    pub output_info: Option<Value>,
+}
+
+impl GuardrailOutput {
+    /// Attach a category to the output
+    pub fn with_category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    /// Attach machine-readable details to the output
+    pub fn with_output_info(mut self, output_info: Value) -> Self {
+        self.output_info = Some(output_info);
+        self
+    }
+}
+
+impl From<GuardrailResult> for GuardrailOutput {
+    fn from(result: GuardrailResult) -> Self {
+        Self {
+            result,
+            category: None,
+            output_info: None,
+        }
+    }
+}
+
+/// What a guardrail checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuardrailKind {
+    /// The input of the run
+    Input,
+    /// Output of an agent, streamed or final
+    Output,
+    /// Arguments of a tool call
+    ToolInput,
+    /// Output of a tool call
+    ToolOutput,
+}
+
+/// Record of one guardrail evaluation during a run
+#[derive(Debug, Clone)]
+pub struct GuardrailEvaluation {
+    /// Name of the guardrail
+    pub guardrail: String,
+    /// What the guardrail checked
+    pub kind: GuardrailKind,
+    /// Agent that was running
+    pub agent_name: String,
+    /// Tool whose call was checked, for tool guardrails
+    pub tool_name: Option<String>,
+    /// Result of the check
+    pub result: GuardrailResult,
+    /// Category reported by the guardrail
+    pub category: Option<String>,
+    /// Details reported by the guardrail
+    pub output_info: Option<Value>,
+}
+
+impl GuardrailEvaluation {
+    /// Whether the guardrail blocked the content
+    pub fn tripped(&self) -> bool {
+        matches!(self.result, GuardrailResult::Block { .. })
+    }
+
+    /// The error reporting this evaluation, which blocked the content for `reason`
+    pub(crate) fn into_error(self, reason: String) -> AgentError {
+        let Self {
+            guardrail,
+            kind,
+            tool_name,
+            category,
+            output_info,
+            ..
+        } = self;
+        let tool_name = tool_name.unwrap_or_default();
+        let output_info = output_info.map(Box::new);
+
+        match kind {
+            GuardrailKind::Input => AgentError::InputGuardrailTriggered {
+                guardrail,
+                reason,
+                category,
+                output_info,
+            },
+            GuardrailKind::Output => AgentError::OutputGuardrailTriggered {
+                guardrail,
+                reason,
+                category,
+                output_info,
+            },
+            GuardrailKind::ToolInput => AgentError::ToolInputGuardrailTriggered {
+                guardrail,
+                tool_name,
+                reason,
+                category,
+                output_info,
+            },
+            GuardrailKind::ToolOutput => AgentError::ToolOutputGuardrailTriggered {
+                guardrail,
+                tool_name,
+                reason,
+                category,
+                output_info,
+            },
+        }
+    }
+}
+
 /// Trait for input guardrails
+///
+/// Input guardrails of the starting agent run before its first model call.
+/// When the input is a list of messages, they check the last user message.
 #[async_trait]
 pub trait InputGuardrail: Send + Sync {
+    /// Name of the guardrail, reported when it trips
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Whether the guardrail runs concurrently with the first model call
+    ///
+    /// The model call is cancelled if the guardrail trips. Such guardrails
+    /// cannot modify the input.
+    fn run_in_parallel(&self) -> bool {
+        false
+    }
+
     /// Check the input and return a guardrail result
-    async fn check(&self, input: &str) -> Result<GuardrailResult>;
+    async fn check(&self, ctx: &RunContext, input: &str) -> Result<GuardrailOutput>;
 }
 
 /// Trait for output guardrails
+///
+/// Output guardrails of the agent producing the final output run on that
+/// output before it is returned.
 #[async_trait]
 pub trait OutputGuardrail: Send + Sync {
+    /// Name of the guardrail, reported when it trips
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
     /// Check the output and return a guardrail result
-    async fn check(&self, output: &str) -> Result<GuardrailResult>;
+    async fn check(&self, ctx: &RunContext, output: &str) -> Result<GuardrailOutput>;
+}
+
+/// How often output guardrails check text while it is being streamed
+///
+/// Streamed text is held back until the guardrails have checked it, so
+/// blocked text never reaches the consumer. `Modify` results are only applied
+/// by the final check of the completed output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuardrailInterval {
+    /// Check whenever at least this many characters are held back
+    EveryChars(usize),
+    /// Check at the end of every sentence or line
+    Sentence,
+}
+
+impl GuardrailInterval {
+    /// Length of the prefix of `pending` that is ready to be checked, if any
+    pub(crate) fn ready_len(&self, pending: &str) -> Option<usize> {
+        match self {
+            Self::EveryChars(chars) => (pending.chars().count() >= *chars).then_some(pending.len()),
+            Self::Sentence => pending.rfind(['.', '!', '?', '\n']).map(|index| index + 1),
+        }
+    }
+}
+
+/// What the runner does when a tool guardrail blocks a call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlockMode {
+    /// Abort the run with a tool guardrail error
+    #[default]
+    Abort,
+    /// Skip the call and send a refusal back to the model as the tool result
+    RejectContent,
 }
 
 /// Trait for tool input guardrails
+///
+/// Tool input guardrails check the arguments of a call before the tool runs.
+/// `Modify` replaces the arguments with `new_content` parsed as JSON.
 #[async_trait]
 pub trait ToolInputGuardrail: Send + Sync {
+    /// Name of the guardrail, reported when it trips
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// What happens when the guardrail blocks a call
+    fn block_mode(&self) -> BlockMode {
+        BlockMode::Abort
+    }
+
     /// Check the tool input
-    async fn check(&self, tool_name: &str, input: &serde_json::Value) -> Result<GuardrailResult>;
+    async fn check(
+        &self,
+        ctx: &RunContext,
+        tool_name: &str,
+        input: &Value,
+    ) -> Result<GuardrailOutput>;
 }
 
 /// Trait for tool output guardrails
+///
+/// Tool output guardrails check the result of a successful call. `Modify`
+/// replaces the result with `new_content`, parsed as JSON when possible.
 #[async_trait]
 pub trait ToolOutputGuardrail: Send + Sync {
+    /// Name of the guardrail, reported when it trips
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// What happens when the guardrail blocks a call
+    fn block_mode(&self) -> BlockMode {
+        BlockMode::Abort
+    }
+
     /// Check the tool output
-    async fn check(&self, tool_name: &str, output: &serde_json::Value) -> Result<GuardrailResult>;
+    async fn check(
+        &self,
+        ctx: &RunContext,
+        tool_name: &str,
+        output: &Value,
+    ) -> Result<GuardrailOutput>;
+}
+
+/// Verdict returned by the judge of a [`JudgeGuardrail`]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GuardrailVerdict {
+    /// Whether the content violates the policy
+    pub tripwire_triggered: bool,
+    /// Why the judge reached its verdict
+    pub reasoning: String,
+}
+
+/// Guardrail that asks a judge model whether content violates a policy
+///
+/// The judge is an agent with [`GuardrailVerdict`] as its output type. When
+/// it triggers the tripwire the content is blocked, with the judge's
+/// reasoning as the reason. The verdict is reported as the output info.
+/// Works as both an input and an output guardrail.
+///
+/// # Example
+///
+/// ```rust
+/// use openai_agents::{Agent, JudgeGuardrail};
+///
+/// let no_medical_advice = JudgeGuardrail::new(
+///     "no_medical_advice",
+///     "The content must not give medical advice.",
+/// )
+/// .model("gpt-4o-mini");
+///
+/// let agent = Agent::builder("Assistant")
+///     .output_guardrail(no_medical_advice)
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct JudgeGuardrail {
+    name: String,
+    judge: Agent,
+    model_provider: Option<Arc<dyn ModelProvider>>,
+    run_in_parallel: bool,
+}
+
+impl JudgeGuardrail {
+    /// Create a judge guardrail enforcing the given policy
+    pub fn new(name: impl Into<String>, policy: impl Into<String>) -> Self {
+        let name = name.into();
+        let instructions = format!(
+            "{}\n\nDecide whether the content you are given violates the policy above. \
+             Set tripwire_triggered to true if it does, and explain your decision in reasoning.",
+            policy.into()
+        );
+        let judge = Agent::builder(format!("{} judge", name))
+            .instructions(instructions)
+            .output_type::<GuardrailVerdict>()
+            .build();
+
+        Self {
+            name,
+            judge,
+            model_provider: None,
+            run_in_parallel: false,
+        }
+    }
+
+    /// Set the model used by the judge
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.judge.model = model.into();
+        self
+    }
+
+    /// Set the model provider used by the judge (defaults to OpenAI)
+    pub fn model_provider(mut self, provider: Arc<dyn ModelProvider>) -> Self {
+        self.model_provider = Some(provider);
+        self
+    }
+
+    /// Run the guardrail concurrently with the first model call, when used on input
+    pub fn run_in_parallel(mut self, parallel: bool) -> Self {
+        self.run_in_parallel = parallel;
+        self
+    }
+
+    /// Ask the judge for a verdict on `content`
+    ///
+    /// Within a run, the judge's model calls count towards the run's usage,
+    /// cost and budget.
+    pub async fn judge(&self, ctx: &RunContext, content: &str) -> Result<GuardrailVerdict> {
+        let config = RunConfig {
+            max_turns: 1,
+            model_override: self.model_provider.clone(),
+            context: ctx.context_arc(),
+            ..Default::default()
+        };
+        Runner::run_nested(&self.judge, content, config, ctx)
+            .await?
+            .final_output_as()
+    }
+
+    async fn evaluate(&self, ctx: &RunContext, content: &str) -> Result<GuardrailOutput> {
+        let verdict = self.judge(ctx, content).await?;
+        let result = if verdict.tripwire_triggered {
+            GuardrailResult::Block {
+                reason: verdict.reasoning.clone(),
+            }
+        } else {
+            GuardrailResult::Allow
+        };
+        Ok(result.with_output_info(serde_json::to_value(verdict)?))
+    }
+}
+
+#[async_trait]
+impl InputGuardrail for JudgeGuardrail {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run_in_parallel(&self) -> bool {
+        self.run_in_parallel
+    }
+
+    async fn check(&self, ctx: &RunContext, input: &str) -> Result<GuardrailOutput> {
+        self.evaluate(ctx, input).await
+    }
+}
+
+#[async_trait]
+impl OutputGuardrail for JudgeGuardrail {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn check(&self, ctx: &RunContext, output: &str) -> Result<GuardrailOutput> {
+        self.evaluate(ctx, output).await
+    }
 }
@@ -3,6 +3,7 @@ use serde_json::{Value, json};
 use std::sync::Arc;
 
 use crate::agent::Agent;
+use crate::context::RunContext;
 use crate::error::Result;
 use crate::tool::Tool;
 
@@ -64,7 +65,7 @@ impl Tool for Handoff {
         })
     }
 
-    async fn execute(&self, _args: Value) -> Result<Value> {
+    async fn execute(&self, _ctx: &RunContext, _args: Value) -> Result<Value> {
         Ok(json!({
             "assistant": self.target_agent.name
         }))
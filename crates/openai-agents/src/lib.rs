@@ -21,44 +21,57 @@
 
 pub mod agent;
 pub mod config;
+pub mod context;
 pub mod error;
 pub mod guardrail;
 pub mod handoff;
 pub mod lifecycle;
 pub mod models;
+pub mod pricing;
 pub mod result;
 pub mod runner;
+pub mod schema;
 pub mod session;
 pub mod stream_events;
 pub mod streaming;
 pub mod tool;
 pub mod tracing_impl;
+pub mod usage;
 
 // Re-exports for convenience
-pub use agent::{Agent, AgentBuilder};
+pub use agent::{Agent, AgentBuilder, AgentLimits};
 pub use config::{get_default_client, set_default_openai_client, set_default_openai_key};
+pub use context::RunContext;
 pub use error::{AgentError, Result};
 pub use guardrail::{
-    GuardrailResult, InputGuardrail, OutputGuardrail, ToolInputGuardrail, ToolOutputGuardrail,
+    BlockMode, GuardrailEvaluation, GuardrailInterval, GuardrailKind, GuardrailOutput,
+    GuardrailResult, GuardrailVerdict, InputGuardrail, JudgeGuardrail, OutputGuardrail,
+    ToolInputGuardrail, ToolOutputGuardrail,
 };
 pub use handoff::Handoff;
 pub use lifecycle::{AgentHooks, RunHooks};
 pub use models::{
-    CompletionRequest, CompletionResponse, ModelProvider, OpenAIChatCompletionsModel,
-    OpenAIResponsesModel,
+    CompletionRequest, CompletionResponse, Message, ModelProvider, OpenAIChatCompletionsModel,
+    OpenAIResponsesModel, ToolCall, ToolChoice,
 };
+pub use pricing::{ModelPrice, PriceTable};
 pub use result::{RunResult, RunResultStreaming};
-pub use runner::{RunConfig, Runner};
-pub use session::{InMemorySession, Session, SessionSettings};
+pub use runner::{RunConfig, RunInput, Runner};
+pub use session::{InMemorySession, Session, SessionSettings, SummarizingSession};
 
 #[cfg(feature = "sqlite-session")]
 pub use session::SqliteSession;
 
+#[cfg(feature = "redis-session")]
+pub use session::RedisSession;
+
 pub use stream_events::{
-    AgentUpdatedEvent, RawResponseEvent, RunItem, RunItemEventName, RunItemStreamEvent, StreamEvent,
+    AgentUpdatedEvent, GuardrailTrippedEvent, RawResponseEvent, RunItem, RunItemEventName,
+    RunItemStreamEvent, StreamEvent,
 };
 pub use streaming::StreamedRunResult;
-pub use tool::{FunctionTool, Tool};
+pub use tool::{FunctionTool, Tool, ToolErrorPolicy};
+pub use usage::{ModelCall, Usage};
 
 // Re-export macros
 pub use openai_agents_macros::function_tool;
@@ -7,26 +7,41 @@ use async_trait::async_trait;
 use serde_json::Value;
 
 use crate::agent::Agent;
+use crate::context::RunContext;
+use crate::error::AgentError;
 use crate::models::{CompletionResponse, Message};
 
 /// Hooks for events related to a specific agent
 #[async_trait]
 pub trait AgentHooks: Send + Sync {
     /// Called before the agent is invoked
-    async fn on_start(&self, agent: &Agent) -> crate::error::Result<()> {
+    async fn on_start(&self, ctx: &RunContext, agent: &Agent) -> crate::error::Result<()> {
+        let _ = ctx;
         let _ = agent;
         Ok(())
     }
 
     /// Called after the agent produces a final output
-    async fn on_end(&self, agent: &Agent, output: &str) -> crate::error::Result<()> {
+    async fn on_end(
+        &self,
+        ctx: &RunContext,
+        agent: &Agent,
+        output: &str,
+    ) -> crate::error::Result<()> {
+        let _ = ctx;
         let _ = agent;
         let _ = output;
         Ok(())
     }
 
     /// Called before the agent makes an LLM call
-    async fn on_llm_start(&self, agent: &Agent, messages: &[Message]) -> crate::error::Result<()> {
+    async fn on_llm_start(
+        &self,
+        ctx: &RunContext,
+        agent: &Agent,
+        messages: &[Message],
+    ) -> crate::error::Result<()> {
+        let _ = ctx;
         let _ = agent;
         let _ = messages;
         Ok(())
@@ -35,21 +50,28 @@ pub trait AgentHooks: Send + Sync {
     /// Called after the agent receives an LLM response
     async fn on_llm_end(
         &self,
+        ctx: &RunContext,
         agent: &Agent,
         response: &CompletionResponse,
     ) -> crate::error::Result<()> {
+        let _ = ctx;
         let _ = agent;
         let _ = response;
         Ok(())
     }
 
     /// Called before a tool is executed
+    ///
+    /// `ctx.tool_call_id()` identifies the call, as it does in the matching
+    /// `on_tool_end` or `on_tool_error` when calls run concurrently.
     async fn on_tool_start(
         &self,
+        ctx: &RunContext,
         agent: &Agent,
         tool_name: &str,
         arguments: &Value,
     ) -> crate::error::Result<()> {
+        let _ = ctx;
         let _ = agent;
         let _ = tool_name;
         let _ = arguments;
@@ -59,18 +81,41 @@ pub trait AgentHooks: Send + Sync {
     /// Called after a tool is executed
     async fn on_tool_end(
         &self,
+        ctx: &RunContext,
         agent: &Agent,
         tool_name: &str,
         result: &Value,
     ) -> crate::error::Result<()> {
+        let _ = ctx;
         let _ = agent;
         let _ = tool_name;
         let _ = result;
         Ok(())
     }
 
+    /// Called when a tool call fails or times out, before the tool error policy is applied
+    async fn on_tool_error(
+        &self,
+        ctx: &RunContext,
+        agent: &Agent,
+        tool_name: &str,
+        error: &AgentError,
+    ) -> crate::error::Result<()> {
+        let _ = ctx;
+        let _ = agent;
+        let _ = tool_name;
+        let _ = error;
+        Ok(())
+    }
+
     /// Called when the agent hands off to another agent
-    async fn on_handoff(&self, from_agent: &Agent, to_agent: &Agent) -> crate::error::Result<()> {
+    async fn on_handoff(
+        &self,
+        ctx: &RunContext,
+        from_agent: &Agent,
+        to_agent: &Agent,
+    ) -> crate::error::Result<()> {
+        let _ = ctx;
         let _ = from_agent;
         let _ = to_agent;
         Ok(())
@@ -81,20 +126,33 @@ pub trait AgentHooks: Send + Sync {
 #[async_trait]
 pub trait RunHooks: Send + Sync {
     /// Called when any agent starts
-    async fn on_agent_start(&self, agent: &Agent) -> crate::error::Result<()> {
+    async fn on_agent_start(&self, ctx: &RunContext, agent: &Agent) -> crate::error::Result<()> {
+        let _ = ctx;
         let _ = agent;
         Ok(())
     }
 
     /// Called when any agent ends
-    async fn on_agent_end(&self, agent: &Agent, output: &str) -> crate::error::Result<()> {
+    async fn on_agent_end(
+        &self,
+        ctx: &RunContext,
+        agent: &Agent,
+        output: &str,
+    ) -> crate::error::Result<()> {
+        let _ = ctx;
         let _ = agent;
         let _ = output;
         Ok(())
     }
 
     /// Called when any handoff occurs
-    async fn on_handoff(&self, from_agent: &Agent, to_agent: &Agent) -> crate::error::Result<()> {
+    async fn on_handoff(
+        &self,
+        ctx: &RunContext,
+        from_agent: &Agent,
+        to_agent: &Agent,
+    ) -> crate::error::Result<()> {
+        let _ = ctx;
         let _ = from_agent;
         let _ = to_agent;
         Ok(())
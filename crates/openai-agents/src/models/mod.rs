@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::error::Result;
+use crate::usage::Usage;
 
 pub mod openai_chat_completions;
 pub mod openai_responses;
@@ -28,9 +29,25 @@ pub struct CompletionRequest {
     pub messages: Vec<Message>,
     pub model: String,
     pub tools: Option<Vec<ToolDefinition>>,
+    /// Whether the model may call several tools in one turn
+    pub parallel_tool_calls: Option<bool>,
     pub max_tokens: Option<u32>,
     pub temperature: Option<f32>,
     pub response_format: Option<ResponseFormat>,
+    /// Whether the model may call the tools (`None` leaves it to the provider)
+    pub tool_choice: Option<ToolChoice>,
+}
+
+/// Whether the model may or must call tools
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolChoice {
+    /// The model decides
+    Auto,
+    /// The model must call a tool
+    Required,
+    /// The model must answer without calling tools
+    None,
 }
 
 /// Response format for structured outputs
@@ -52,10 +69,88 @@ pub struct JsonSchemaFormat {
 }
 
 /// A message in the conversation
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Message {
-    pub role: String,
-    pub content: String,
+///
+/// Messages serialize tagged by `role`, which is also how they are stored in
+/// sessions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "role", rename_all = "snake_case")]
+pub enum Message {
+    /// Instructions for the model
+    System { content: String },
+    /// Input from the user
+    User { content: String },
+    /// Output of the model, possibly requesting tool calls
+    Assistant {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tool_calls: Vec<ToolCall>,
+    },
+    /// Result of a tool call, answering the call with the same id
+    Tool {
+        tool_call_id: String,
+        content: String,
+    },
+}
+
+impl Message {
+    /// Create a system message
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::System {
+            content: content.into(),
+        }
+    }
+
+    /// Create a user message
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::User {
+            content: content.into(),
+        }
+    }
+
+    /// Create an assistant message without tool calls
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::Assistant {
+            content: Some(content.into()),
+            tool_calls: Vec::new(),
+        }
+    }
+
+    /// Create an assistant message requesting the given tool calls
+    pub fn assistant_tool_calls(content: Option<String>, tool_calls: Vec<ToolCall>) -> Self {
+        Self::Assistant {
+            content,
+            tool_calls,
+        }
+    }
+
+    /// Create a tool result message for the call with the given id
+    pub fn tool(tool_call_id: impl Into<String>, content: impl Into<String>) -> Self {
+        Self::Tool {
+            tool_call_id: tool_call_id.into(),
+            content: content.into(),
+        }
+    }
+
+    /// Role of the message (`system`, `user`, `assistant` or `tool`)
+    pub fn role(&self) -> &'static str {
+        match self {
+            Self::System { .. } => "system",
+            Self::User { .. } => "user",
+            Self::Assistant { .. } => "assistant",
+            Self::Tool { .. } => "tool",
+        }
+    }
+
+    /// Text content of the message, if any
+    pub fn content(&self) -> Option<&str> {
+        match self {
+            Self::System { content } | Self::User { content } | Self::Tool { content, .. } => {
+                Some(content)
+            }
+            Self::Assistant { content, .. } => content.as_deref(),
+        }
+    }
 }
 
 /// Tool definition for function calling
@@ -72,10 +167,12 @@ pub struct CompletionResponse {
     pub content: Option<String>,
     pub tool_calls: Vec<ToolCall>,
     pub finish_reason: Option<String>,
+    /// Tokens used by the call, if the provider reported them
+    pub usage: Option<Usage>,
 }
 
 /// A tool call from the model
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ToolCall {
     pub id: String,
     pub name: String,
@@ -96,6 +193,8 @@ pub struct StreamChunk {
     pub tool_call_deltas: Vec<ToolCallDelta>,
     /// Finish reason if this is the last chunk
     pub finish_reason: Option<String>,
+    /// Tokens used by the whole call, usually reported by the last chunk
+    pub usage: Option<Usage>,
 }
 
 /// A delta for a tool call
@@ -4,11 +4,13 @@ use async_openai::{
     Client,
     config::OpenAIConfig,
     types::chat::{
-        ChatCompletionMessageToolCalls, ChatCompletionRequestAssistantMessageArgs,
-        ChatCompletionRequestMessage, ChatCompletionRequestSystemMessageArgs,
-        ChatCompletionRequestUserMessageArgs, ChatCompletionTool, ChatCompletionTools,
+        ChatCompletionMessageToolCall, ChatCompletionMessageToolCalls,
+        ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestMessage,
+        ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestToolMessageArgs,
+        ChatCompletionRequestUserMessageArgs, ChatCompletionStreamOptions, ChatCompletionTool,
+        ChatCompletionToolChoiceOption, ChatCompletionTools, CompletionUsage,
         CreateChatCompletionRequest, CreateChatCompletionRequestArgs, CreateChatCompletionResponse,
-        FunctionObject, ResponseFormat, ResponseFormatJsonSchema,
+        FunctionCall, FunctionObject, ResponseFormat, ResponseFormatJsonSchema, ToolChoiceOptions,
     },
 };
 
@@ -17,7 +19,11 @@ use async_trait::async_trait;
 use crate::{
     config::get_default_client,
     error::{AgentError, Result},
-    models::{CompletionRequest, CompletionResponse, CompletionStream, ModelProvider, ToolCall},
+    models::{
+        CompletionRequest, CompletionResponse, CompletionStream, Message, ModelProvider, ToolCall,
+        ToolChoice,
+    },
+    usage::Usage,
 };
 
 /// OpenAI Responses API model provider
@@ -50,24 +56,50 @@ impl OpenAIResponsesModel {
         let messages: Vec<ChatCompletionRequestMessage> = request
             .messages
             .into_iter()
-            .map(|m| match m.role.as_str() {
-                "user" => ChatCompletionRequestUserMessageArgs::default()
-                    .content(m.content)
-                    .build()
-                    .unwrap()
-                    .into(),
-                "assistant" => ChatCompletionRequestAssistantMessageArgs::default()
-                    .content(m.content)
+            .map(|m| match m {
+                Message::System { content } => ChatCompletionRequestSystemMessageArgs::default()
+                    .content(content)
                     .build()
                     .unwrap()
                     .into(),
-                "system" => ChatCompletionRequestSystemMessageArgs::default()
-                    .content(m.content)
+                Message::User { content } => ChatCompletionRequestUserMessageArgs::default()
+                    .content(content)
                     .build()
                     .unwrap()
                     .into(),
-                _ => ChatCompletionRequestUserMessageArgs::default()
-                    .content(m.content)
+                Message::Assistant {
+                    content,
+                    tool_calls,
+                } => {
+                    let mut args = ChatCompletionRequestAssistantMessageArgs::default();
+                    if let Some(content) = content {
+                        args.content(content);
+                    }
+                    if !tool_calls.is_empty() {
+                        let tool_calls: Vec<ChatCompletionMessageToolCalls> = tool_calls
+                            .into_iter()
+                            .map(|call| {
+                                ChatCompletionMessageToolCalls::Function(
+                                    ChatCompletionMessageToolCall {
+                                        id: call.id,
+                                        function: FunctionCall {
+                                            name: call.name,
+                                            arguments: call.arguments.to_string(),
+                                        },
+                                    },
+                                )
+                            })
+                            .collect();
+                        args.tool_calls(tool_calls);
+                    }
+                    args.build().unwrap().into()
+                }
+                Message::Tool {
+                    tool_call_id,
+                    content,
+                } => ChatCompletionRequestToolMessageArgs::default()
+                    .tool_call_id(tool_call_id)
+                    .content(content)
                     .build()
                     .unwrap()
                     .into(),
@@ -92,6 +124,18 @@ impl OpenAIResponsesModel {
                 })
                 .collect();
             builder.tools(openai_tools);
+
+            // Only valid alongside tools
+            if let Some(parallel_tool_calls) = request.parallel_tool_calls {
+                builder.parallel_tool_calls(parallel_tool_calls);
+            }
+            if let Some(tool_choice) = request.tool_choice {
+                builder.tool_choice(ChatCompletionToolChoiceOption::Mode(match tool_choice {
+                    ToolChoice::Auto => ToolChoiceOptions::Auto,
+                    ToolChoice::Required => ToolChoiceOptions::Required,
+                    ToolChoice::None => ToolChoiceOptions::None,
+                }));
+            }
         }
 
         if let Some(format) = request.response_format {
@@ -149,10 +193,33 @@ impl OpenAIResponsesModel {
             content,
             tool_calls,
             finish_reason: choice.finish_reason.as_ref().map(|r| format!("{:?}", r)),
+            usage: response.usage.as_ref().map(convert_usage),
         })
     }
 }
 
+/// Token usage of one call as reported by the API
+fn convert_usage(usage: &CompletionUsage) -> Usage {
+    Usage {
+        requests: 1,
+        prompt_tokens: usage.prompt_tokens.into(),
+        completion_tokens: usage.completion_tokens.into(),
+        cached_tokens: usage
+            .prompt_tokens_details
+            .as_ref()
+            .and_then(|details| details.cached_tokens)
+            .unwrap_or_default()
+            .into(),
+        reasoning_tokens: usage
+            .completion_tokens_details
+            .as_ref()
+            .and_then(|details| details.reasoning_tokens)
+            .unwrap_or_default()
+            .into(),
+        total_tokens: usage.total_tokens.into(),
+    }
+}
+
 impl Default for OpenAIResponsesModel {
     fn default() -> Self {
         Self::new()
@@ -180,8 +247,12 @@ impl ModelProvider for OpenAIResponsesModel {
 
         let mut openai_request = self.convert_request(request);
 
-        // Enable streaming
+        // Enable streaming, with the usage reported in a final chunk
         openai_request.stream = Some(true);
+        openai_request.stream_options = Some(ChatCompletionStreamOptions {
+            include_usage: Some(true),
+            include_obfuscation: None,
+        });
 
         let stream = self
             .client
@@ -227,6 +298,7 @@ impl ModelProvider for OpenAIResponsesModel {
                         delta,
                         tool_call_deltas,
                         finish_reason,
+                        usage: response.usage.as_ref().map(convert_usage),
                     }
                 })
         });
@@ -234,3 +306,58 @@ impl ModelProvider for OpenAIResponsesModel {
         Ok(CompletionStream::new(converted_stream))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_convert_request_with_tool_call_and_result() {
+        let request = CompletionRequest {
+            messages: vec![
+                Message::user("Where is my order?"),
+                Message::assistant_tool_calls(
+                    None,
+                    vec![ToolCall {
+                        id: "call_1".to_string(),
+                        name: "lookup".to_string(),
+                        arguments: json!({"order": 42}),
+                    }],
+                ),
+                Message::tool("call_1", "shipped"),
+            ],
+            model: "gpt-4o".to_string(),
+            tools: None,
+            parallel_tool_calls: None,
+            max_tokens: None,
+            temperature: None,
+            response_format: None,
+            tool_choice: None,
+        };
+
+        let model = OpenAIResponsesModel::with_api_key("test");
+        let body = serde_json::to_value(model.convert_request(request)).unwrap();
+
+        // Arguments travel as a JSON string, and the result refers to its call
+        assert_eq!(
+            body,
+            json!({
+                "model": "gpt-4o",
+                "messages": [
+                    {"role": "user", "content": "Where is my order?"},
+                    {
+                        "role": "assistant",
+                        "tool_calls": [{
+                            "id": "call_1",
+                            "type": "function",
+                            "function": {"name": "lookup", "arguments": "{\"order\":42}"}
+                        }]
+                    },
+                    {"role": "tool", "tool_call_id": "call_1", "content": "shipped"}
+                ]
+            })
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Cost estimation from token usage
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AgentError, Result};
+use crate::usage::Usage;
+
+/// Prices of a model in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// Price of prompt tokens
+    pub input: f64,
+    /// Price of prompt tokens served from the cache (defaults to `input`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_input: Option<f64>,
+    /// Price of completion tokens, including reasoning tokens
+    pub output: f64,
+}
+
+impl ModelPrice {
+    /// Create a price without a cached input discount
+    pub fn new(input: f64, output: f64) -> Self {
+        Self {
+            input,
+            cached_input: None,
+            output,
+        }
+    }
+
+    /// Set the price of cached prompt tokens
+    pub fn with_cached_input(mut self, cached_input: f64) -> Self {
+        self.cached_input = Some(cached_input);
+        self
+    }
+
+    /// Estimated cost in USD of `usage`
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        let cached = usage.cached_tokens.min(usage.prompt_tokens);
+        let uncached = usage.prompt_tokens - cached;
+        let per_token = |tokens: u64, price: f64| tokens as f64 * price / 1_000_000.0;
+
+        per_token(uncached, self.input)
+            + per_token(cached, self.cached_input.unwrap_or(self.input))
+            + per_token(usage.completion_tokens, self.output)
+    }
+}
+
+/// Model prices keyed by model name
+///
+/// Serialized as a map from model name to [`ModelPrice`], e.g. in TOML:
+///
+/// ```toml
+/// [gpt-4o]
+/// input = 2.5
+/// cached_input = 1.25
+/// output = 10.0
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PriceTable {
+    prices: BTreeMap<String, ModelPrice>,
+}
+
+impl PriceTable {
+    /// Create an empty price table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the price of a model
+    pub fn with_price(mut self, model: impl Into<String>, price: ModelPrice) -> Self {
+        self.prices.insert(model.into(), price);
+        self
+    }
+
+    /// Parse a price table from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Parse a price table from TOML
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        toml::from_str(toml).map_err(|e| AgentError::SerializationError(e.to_string()))
+    }
+
+    /// Load a price table from a `.toml` or `.json` file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            AgentError::ConfigError(format!(
+                "Failed to read price table {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&contents),
+            _ => Self::from_json(&contents),
+        }
+    }
+
+    /// Price of a model, matched by its exact name
+    pub fn price(&self, model: &str) -> Option<&ModelPrice> {
+        self.prices.get(model)
+    }
+
+    /// Estimated cost in USD of `usage` by `model`, if the model has a price
+    pub fn cost(&self, model: &str, usage: &Usage) -> Option<f64> {
+        self.price(model).map(|price| price.cost(usage))
+    }
+}
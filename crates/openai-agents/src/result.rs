@@ -1,11 +1,28 @@
 //! Result types for agent runs
 
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::agent::Agent;
+use crate::guardrail::GuardrailEvaluation;
+use crate::models::{CompletionResponse, Message};
+use crate::stream_events::RunItem;
+use crate::usage::{ModelCall, RunUsage, Usage};
+
 /// Result of running an agent
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct RunResult {
     final_output: String,
     structured_output: Option<serde_json::Value>,
-    // TODO: Add more fields (usage, traces, etc.)
+    guardrail_evaluations: Vec<GuardrailEvaluation>,
+    usage: RunUsage,
+    new_items: Vec<RunItem>,
+    raw_responses: Vec<CompletionResponse>,
+    input: Vec<Message>,
+    new_messages: Vec<Message>,
+    last_agent: Option<Arc<Agent>>,
+    // TODO: Add more fields (traces, etc.)
 }
 
 impl RunResult {
@@ -14,14 +31,21 @@ impl RunResult {
         Self {
             final_output: final_output.into(),
             structured_output: None,
+            guardrail_evaluations: Vec::new(),
+            usage: RunUsage::default(),
+            new_items: Vec::new(),
+            raw_responses: Vec::new(),
+            input: Vec::new(),
+            new_messages: Vec::new(),
+            last_agent: None,
         }
     }
 
     /// Create a new run result with structured output
     pub fn with_structured(final_output: impl Into<String>, structured: serde_json::Value) -> Self {
         Self {
-            final_output: final_output.into(),
             structured_output: Some(structured),
+            ..Self::new(final_output)
         }
     }
 
@@ -35,6 +59,102 @@ impl RunResult {
         self.structured_output.as_ref()
     }
 
+    /// Every guardrail evaluation of the run, passed and tripped, in order
+    pub fn guardrail_evaluations(&self) -> &[GuardrailEvaluation] {
+        &self.guardrail_evaluations
+    }
+
+    pub(crate) fn with_guardrail_evaluations(
+        mut self,
+        evaluations: Vec<GuardrailEvaluation>,
+    ) -> Self {
+        self.guardrail_evaluations = evaluations;
+        self
+    }
+
+    /// Tokens used by all model calls of the run
+    pub fn usage(&self) -> Usage {
+        self.usage.total
+    }
+
+    /// Tokens used by the model calls of each agent, keyed by agent name
+    pub fn usage_by_agent(&self) -> &BTreeMap<String, Usage> {
+        &self.usage.by_agent
+    }
+
+    /// Usage and estimated cost of every model call of the run, in order
+    pub fn model_calls(&self) -> &[ModelCall] {
+        &self.usage.calls
+    }
+
+    /// Estimated cost in USD of the run, if any model called has a price in
+    /// [`RunConfig::price_table`](crate::RunConfig::price_table)
+    pub fn cost(&self) -> Option<f64> {
+        self.usage.cost()
+    }
+
+    pub(crate) fn with_usage(mut self, usage: RunUsage) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    /// Items generated by the run, in order: messages, tool calls, tool
+    /// outputs and handoffs
+    pub fn new_items(&self) -> &[RunItem] {
+        &self.new_items
+    }
+
+    pub(crate) fn with_new_items(mut self, items: Vec<RunItem>) -> Self {
+        self.new_items = items;
+        self
+    }
+
+    /// Every response of the model during the run, in order
+    ///
+    /// A response whose tool calls were rejected by a tool call limit is left
+    /// out, though its usage still counts towards [`usage`](Self::usage).
+    pub fn raw_responses(&self) -> &[CompletionResponse] {
+        &self.raw_responses
+    }
+
+    pub(crate) fn with_raw_responses(mut self, responses: Vec<CompletionResponse>) -> Self {
+        self.raw_responses = responses;
+        self
+    }
+
+    /// The agent that produced the final output, which may differ from the
+    /// starting agent after handoffs (`None` for results not made by a run)
+    pub fn last_agent(&self) -> Option<&Arc<Agent>> {
+        self.last_agent.as_ref()
+    }
+
+    pub(crate) fn with_last_agent(mut self, agent: Arc<Agent>) -> Self {
+        self.last_agent = Some(agent);
+        self
+    }
+
+    /// The input of the run followed by the messages it generated
+    ///
+    /// Passing this, plus a new user message, as the input of the next run
+    /// continues the conversation without a session.
+    pub fn to_input_list(&self) -> Vec<Message> {
+        self.input
+            .iter()
+            .chain(&self.new_messages)
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn with_conversation(
+        mut self,
+        input: Vec<Message>,
+        new_messages: Vec<Message>,
+    ) -> Self {
+        self.input = input;
+        self.new_messages = new_messages;
+        self
+    }
+
     /// Parse the final output as a specific type
     pub fn final_output_as<T: serde::de::DeserializeOwned>(&self) -> crate::error::Result<T> {
         if let Some(structured) = &self.structured_output {
@@ -47,6 +167,22 @@ impl RunResult {
     }
 }
 
+impl fmt::Debug for RunResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RunResult")
+            .field("final_output", &self.final_output)
+            .field("structured_output", &self.structured_output)
+            .field("guardrail_evaluations", &self.guardrail_evaluations)
+            .field("usage", &self.usage)
+            .field("new_items", &self.new_items)
+            .field("raw_responses", &self.raw_responses)
+            .field("input", &self.input)
+            .field("new_messages", &self.new_messages)
+            .field("last_agent", &self.last_agent.as_ref().map(|a| &a.name))
+            .finish()
+    }
+}
+
 /// Streaming result of running an agent
 pub struct RunResultStreaming {
     // TODO: Implement streaming result
@@ -1,14 +1,37 @@
 //! Runner for executing agents
 
-use std::sync::Arc;
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::{FutureExt, StreamExt, TryStreamExt};
+use serde_json::Value;
+use tokio::sync::mpsc;
 
 use crate::{
     agent::Agent,
+    context::RunContext,
     error::{AgentError, Result},
-    models::{CompletionRequest, Message, ModelProvider, OpenAIResponsesModel, ToolDefinition},
+    guardrail::{
+        BlockMode, GuardrailEvaluation, GuardrailInterval, GuardrailKind, GuardrailOutput,
+        GuardrailResult, ToolInputGuardrail, ToolOutputGuardrail,
+    },
+    handoff::Handoff,
+    models::{
+        CompletionRequest, CompletionResponse, Message, ModelProvider, OpenAIResponsesModel,
+        ToolCall, ToolChoice, ToolDefinition,
+    },
+    pricing::PriceTable,
     result::RunResult,
-    session::Session,
-    tool::Tool,
+    session::{Session, SessionSettings},
+    stream_events::{
+        AgentUpdatedEvent, GuardrailTrippedEvent, RawResponseEvent, RunItem, RunItemEventName,
+        RunItemStreamEvent, StreamEvent,
+    },
+    tool::{Tool, ToolErrorPolicy},
+    usage::{ModelCall, RunUsage, Usage, UsageMeter},
 };
 
 /// Configuration for running an agent
@@ -22,6 +45,38 @@ pub struct RunConfig {
     pub model_override: Option<Arc<dyn ModelProvider>>,
     /// Global lifecycle hooks for the run
     pub run_hooks: Vec<Arc<dyn crate::lifecycle::RunHooks>>,
+    /// Application context handed to tools, hooks and guardrails via [`RunContext`]
+    pub context: Arc<dyn Any + Send + Sync>,
+    /// How failing tool calls are handled, unless the agent overrides it
+    pub tool_error_policy: ToolErrorPolicy,
+    /// Maximum number of tool calls of one turn executed concurrently
+    /// (`None` runs all of them at once)
+    pub max_concurrent_tool_calls: Option<usize>,
+    /// Default timeout for tools that don't set their own
+    pub tool_timeout: Option<Duration>,
+    /// Guardrails checking the arguments of every tool call of the run
+    pub tool_input_guardrails: Vec<Arc<dyn ToolInputGuardrail>>,
+    /// Guardrails checking the output of every tool call of the run
+    pub tool_output_guardrails: Vec<Arc<dyn ToolOutputGuardrail>>,
+    /// How often output guardrails check streamed text before it is released
+    /// (`None` only checks the final output)
+    pub output_guardrail_interval: Option<GuardrailInterval>,
+    /// Prices used to estimate the cost of every model call
+    pub price_table: Option<Arc<PriceTable>>,
+    /// Budget in USD: the run stops with [`AgentError::BudgetExceeded`] once
+    /// its estimated spend exceeds it (requires a price for every model called)
+    pub max_cost: Option<f64>,
+    /// How much session history is sent to the model, overriding the
+    /// session's own [`Session::settings`]
+    pub session_settings: Option<SessionSettings>,
+}
+
+impl RunConfig {
+    /// Set the application context of the run
+    pub fn with_context<T: Any + Send + Sync>(mut self, context: T) -> Self {
+        self.context = Arc::new(context);
+        self
+    }
 }
 
 impl Default for RunConfig {
@@ -31,10 +86,99 @@ impl Default for RunConfig {
             session: None,
             model_override: None,
             run_hooks: Vec::new(),
+            context: Arc::new(()),
+            tool_error_policy: ToolErrorPolicy::default(),
+            max_concurrent_tool_calls: None,
+            tool_timeout: None,
+            tool_input_guardrails: Vec::new(),
+            tool_output_guardrails: Vec::new(),
+            output_guardrail_interval: None,
+            price_table: None,
+            max_cost: None,
+            session_settings: None,
+        }
+    }
+}
+
+/// Input of a run: a user message, or a conversation to continue
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use openai_agents::{Agent, Message, Runner};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let agent = Agent::builder("Assistant").build();
+/// let result = Runner::run(&agent, "My name is Ama.").await?;
+///
+/// // Continue on the agent that answered, with the conversation so far
+/// let mut input = result.to_input_list();
+/// input.push(Message::user("What is my name?"));
+/// let agent = result.last_agent().unwrap();
+/// let result = Runner::run(agent, input).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunInput {
+    /// A single user message
+    Text(String),
+    /// Conversation messages, such as [`RunResult::to_input_list`] followed by
+    /// a new user message
+    Messages(Vec<Message>),
+}
+
+impl RunInput {
+    fn into_messages(self) -> Vec<Message> {
+        match self {
+            Self::Text(text) => vec![Message::user(text)],
+            Self::Messages(messages) => messages,
         }
     }
 }
 
+impl From<&str> for RunInput {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<String> for RunInput {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&String> for RunInput {
+    fn from(text: &String) -> Self {
+        Self::Text(text.clone())
+    }
+}
+
+impl From<Cow<'_, str>> for RunInput {
+    fn from(text: Cow<'_, str>) -> Self {
+        Self::Text(text.into_owned())
+    }
+}
+
+impl From<Box<str>> for RunInput {
+    fn from(text: Box<str>) -> Self {
+        Self::Text(text.into())
+    }
+}
+
+impl From<Vec<Message>> for RunInput {
+    fn from(messages: Vec<Message>) -> Self {
+        Self::Messages(messages)
+    }
+}
+
+impl From<&[Message]> for RunInput {
+    fn from(messages: &[Message]) -> Self {
+        Self::Messages(messages.to_vec())
+    }
+}
+
 /// Runner for executing agents
 pub struct Runner;
 
@@ -56,650 +200,1092 @@ impl Runner {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn run(agent: &Agent, input: impl Into<String>) -> Result<RunResult> {
+    pub async fn run(agent: &Agent, input: impl Into<RunInput>) -> Result<RunResult> {
         Self::run_with_config(agent, input, RunConfig::default()).await
     }
 
     /// Run an agent with custom configuration
+    ///
+    /// This drives the same run loop as [`Runner::run_streamed_with_config`],
+    /// within the returned future: dropping it (on a timeout, say) stops the
+    /// run.
     pub async fn run_with_config(
         agent: &Agent,
-        input: impl Into<String>,
+        input: impl Into<RunInput>,
         config: RunConfig,
     ) -> Result<RunResult> {
-        let input = input.into();
-        let mut messages = Vec::new();
+        let usage = Arc::new(Mutex::new(RunUsage::default()));
+        Self::run_inline(agent, input.into(), config, usage).await
+    }
 
-        // Add system message if agent has instructions
-        if !agent.instructions.is_empty() {
-            messages.push(Message {
-                role: "system".to_string(),
-                content: agent.instructions.clone(),
-            });
-        }
+    /// Run an agent with streaming enabled
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use openai_agents::{Agent, Runner};
+    /// use futures::StreamExt;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let agent = Agent::builder("Assistant")
+    ///     .instructions("You are helpful.")
+    ///     .build();
+    ///
+    /// let result = Runner::run_streamed(&agent, "Hello!").await?;
+    ///
+    /// // Stream events
+    /// let mut stream = result.stream_events();
+    /// while let Some(event) = stream.next().await {
+    ///     // Process event
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run_streamed(
+        agent: &Agent,
+        input: impl Into<RunInput>,
+    ) -> Result<crate::streaming::StreamedRunResult> {
+        Self::run_streamed_with_config(agent, input, RunConfig::default()).await
+    }
 
-        // Load session history if available
-        if let Some(session) = &config.session {
-            let history = session.get_items(None).await?;
-            for item in history {
-                if let Ok(msg) = serde_json::from_value::<Message>(item) {
-                    messages.push(msg);
+    /// Run an agent with streaming and custom configuration
+    pub async fn run_streamed_with_config(
+        agent: &Agent,
+        input: impl Into<RunInput>,
+        config: RunConfig,
+    ) -> Result<crate::streaming::StreamedRunResult> {
+        let usage = Arc::new(Mutex::new(RunUsage::default()));
+        Ok(Self::spawn(agent, input.into(), config, usage))
+    }
+
+    /// Run an agent on behalf of the run of `ctx`, if any
+    ///
+    /// The nested run's model calls count towards the usage of that run and
+    /// are priced and budgeted by its configuration.
+    pub(crate) async fn run_nested(
+        agent: &Agent,
+        input: impl Into<RunInput>,
+        mut config: RunConfig,
+        ctx: &RunContext,
+    ) -> Result<RunResult> {
+        let Some(meter) = ctx.usage_meter() else {
+            return Self::run_with_config(agent, input, config).await;
+        };
+        config.price_table = meter.price_table.clone();
+        config.max_cost = meter.max_cost;
+        Self::run_inline(agent, input.into(), config, meter.usage.clone()).await
+    }
+
+    /// Drive the run loop in the calling task, accounting its usage in `usage`
+    async fn run_inline(
+        agent: &Agent,
+        input: RunInput,
+        config: RunConfig,
+        usage: Arc<Mutex<RunUsage>>,
+    ) -> Result<RunResult> {
+        // Nobody listens to the events of a blocking run
+        let (tx, _) = mpsc::unbounded_channel();
+        let model = Self::model(&config);
+        RunLoop::new(agent, config, model, tx, usage)
+            .run(input)
+            .await
+    }
+
+    /// Start the run loop in a task, accounting its usage in `usage`
+    fn spawn(
+        agent: &Agent,
+        input: RunInput,
+        config: RunConfig,
+        usage: Arc<Mutex<RunUsage>>,
+    ) -> crate::streaming::StreamedRunResult {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let (streamed_result, shared_state) =
+            crate::streaming::StreamedRunResult::new(rx, usage.clone());
+
+        // Spawn a task to run the agent and emit events
+        let model = Self::model(&config);
+        let mut run = RunLoop::new(agent, config, model, tx, usage);
+        tokio::spawn(async move {
+            let result = run.run(input).await;
+
+            // Set the outcome before the event channel closes: the loop owns
+            // the sender, so it is only dropped once the state is written
+            {
+                let mut state = shared_state.lock().unwrap();
+                match result {
+                    Ok(result) => state.final_result = Some(result),
+                    Err(error) => state.error = Some(error),
                 }
             }
+            drop(run);
+        });
+
+        streamed_result
+    }
+
+    /// The model provider of a run: the override, or OpenAI
+    fn model(config: &RunConfig) -> Arc<dyn ModelProvider> {
+        match &config.model_override {
+            Some(model) => model.clone(),
+            None => Arc::new(OpenAIResponsesModel::new()),
         }
+    }
+}
 
-        // Add user input
-        messages.push(Message {
-            role: "user".to_string(),
-            content: input.clone(),
-        });
+/// The agent loop shared by the blocking and streaming entry points
+struct RunLoop {
+    config: RunConfig,
+    model: Arc<dyn ModelProvider>,
+    events: mpsc::UnboundedSender<StreamEvent>,
+    agent: Arc<Agent>,
+    ctx: RunContext,
+    tools: Option<Vec<ToolDefinition>>,
+    messages: Vec<Message>,
+    /// Whether `messages` starts with the system message the run inserted
+    /// for the current agent's instructions
+    instructions_message: bool,
+    guardrail_log: GuardrailLog,
+    usage: Arc<Mutex<RunUsage>>,
+    /// Turns and tool calls of each agent, keyed by agent name
+    counts: HashMap<String, AgentCounts>,
+    /// Position of the run's input in `messages`, after the session history
+    input_start: usize,
+    input_len: usize,
+    items: Vec<RunItem>,
+    raw_responses: Vec<CompletionResponse>,
+}
 
-        // Initialize model provider
-        let model: Arc<dyn ModelProvider> = if let Some(m) = config.model_override.clone() {
-            m
-        } else {
-            Arc::new(OpenAIResponsesModel::new())
+/// What an agent did so far, to check it against its limits
+#[derive(Default)]
+struct AgentCounts {
+    turns: usize,
+    tool_calls: usize,
+}
+
+impl RunLoop {
+    fn new(
+        agent: &Agent,
+        config: RunConfig,
+        model: Arc<dyn ModelProvider>,
+        events: mpsc::UnboundedSender<StreamEvent>,
+        usage: Arc<Mutex<RunUsage>>,
+    ) -> Self {
+        let meter = UsageMeter {
+            usage: usage.clone(),
+            price_table: config.price_table.clone(),
+            max_cost: config.max_cost,
         };
+        let ctx = RunContext::from_arc(config.context.clone())
+            .with_session(config.session.clone())
+            .with_usage_meter(meter)
+            .for_agent(&agent.name);
+
+        Self {
+            config,
+            model,
+            events,
+            agent: Arc::new(agent.clone()),
+            ctx,
+            tools: tool_definitions(agent),
+            messages: Vec::new(),
+            instructions_message: false,
+            guardrail_log: GuardrailLog::default(),
+            usage,
+            counts: HashMap::new(),
+            input_start: 0,
+            input_len: 0,
+            items: Vec::new(),
+            raw_responses: Vec::new(),
+        }
+    }
 
-        // Convert agent tools and handoffs to tool definitions
-        let mut tool_definitions = Vec::new();
+    /// Run the agent on `input` until it produces a final output
+    async fn run(&mut self, input: RunInput) -> Result<RunResult> {
+        let result = self.run_to_completion(input).await;
+        result.map_err(|e| self.tripped(e))
+    }
 
-        for tool in &agent.tools {
-            tool_definitions.push(ToolDefinition {
-                name: tool.name().to_string(),
-                description: tool.description().to_string(),
-                parameters: tool.parameters_schema(),
-            });
+    async fn run_to_completion(&mut self, input: RunInput) -> Result<RunResult> {
+        // Add system message if agent has instructions
+        if !self.agent.instructions.is_empty() {
+            self.messages
+                .push(Message::system(self.agent.instructions.clone()));
+            self.instructions_message = true;
         }
 
-        for handoff in &agent.handoffs {
-            tool_definitions.push(ToolDefinition {
-                name: handoff.name().to_string(),
-                description: handoff.description().to_string(),
-                parameters: handoff.parameters_schema(),
-            });
+        // Load session history if available
+        if let Some(session) = &self.config.session {
+            let settings = self
+                .config
+                .session_settings
+                .as_ref()
+                .or_else(|| session.settings())
+                .cloned()
+                .unwrap_or_default();
+            for item in session.get_history(&settings).await? {
+                if let Ok(msg) = serde_json::from_value::<Message>(item) {
+                    self.messages.push(msg);
+                }
+            }
         }
 
-        let tools = if !tool_definitions.is_empty() {
-            Some(tool_definitions)
-        } else {
-            None
+        // Check the latest user message against the starting agent's guardrails
+        let log = self.guardrail_log.clone();
+        let mut input = input.into_messages();
+        let checked = match last_user_content(&mut input) {
+            Some(content) => {
+                let checked = std::mem::take(content);
+                *content =
+                    run_input_guardrails(&self.agent, &self.ctx, &log, checked, false).await?;
+                Some(content.clone())
+            }
+            None => None,
         };
 
-        // We use Arc to manage the current agent so we can easily swap it during handoffs
-        let mut current_agent: Arc<Agent> = Arc::new(agent.clone());
-        let mut current_tools = tools;
+        // Add the input to the conversation
+        self.input_start = self.messages.len();
+        self.input_len = input.len();
+        self.messages.extend(input);
 
-        // Main agent loop
-        for turn in 0..config.max_turns {
-            // Trigger on_agent_start and on_start hooks
-            for hook in &config.run_hooks {
-                hook.on_agent_start(&current_agent).await?;
-            }
-            for hook in &current_agent.hooks {
-                hook.on_start(&current_agent).await?;
+        for turn in 0.. {
+            if let Some(error) = self.turn_limit(turn) {
+                let output = self.final_answer(error).await?;
+                return self.finish(output).await;
             }
 
-            let request = CompletionRequest {
-                messages: messages.clone(),
-                model: current_agent.model.clone(),
-                tools: current_tools.clone(),
-                max_tokens: None,
-                temperature: None,
-                response_format: current_agent.output_schema.as_ref().map(|schema| {
-                    crate::models::ResponseFormat::JsonSchema {
-                        json_schema: crate::models::JsonSchemaFormat {
-                            name: current_agent
-                                .output_name
-                                .clone()
-                                .unwrap_or_else(|| "output".to_string()),
-                            description: None,
-                            schema: schema.clone(),
-                            strict: Some(true),
-                        },
+            let response = if turn == 0 {
+                // Parallel input guardrails race the first model call, which is
+                // dropped as soon as one of them trips
+                let (agent, ctx) = (self.agent.clone(), self.ctx.clone());
+                let guardrails = async {
+                    match checked.clone() {
+                        Some(input) => run_input_guardrails(&agent, &ctx, &log, input, true)
+                            .await
+                            .map(drop),
+                        None => Ok(()),
                     }
-                }),
+                };
+                futures::future::try_join(guardrails, self.run_turn(None))
+                    .await?
+                    .1
+            } else {
+                self.run_turn(None).await?
             };
 
-            // Trigger on_llm_start hooks
-            for hook in &current_agent.hooks {
-                hook.on_llm_start(&current_agent, &messages).await?;
+            if response.tool_calls.is_empty() {
+                let output = response.content.unwrap_or_default();
+                return self.finish(output).await;
             }
 
-            let response = model.complete(request).await?;
-
-            // Trigger on_llm_end hooks
-            for hook in &current_agent.hooks {
-                hook.on_llm_end(&current_agent, &response).await?;
+            if let Some(error) = self.count_tool_calls(&response.tool_calls) {
+                // The calls won't be made, so the response must not stay in
+                // the transcript, the items or the responses of the run
+                self.messages.pop();
+                self.raw_responses.pop();
+                if response.content.is_some() {
+                    self.items.pop();
+                }
+                let output = self.final_answer(error).await?;
+                return self.finish(output).await;
             }
 
-            // Check if we have a final output (no tool calls)
-            if let Some(content) = &response.content {
-                if response.tool_calls.is_empty() {
-                    // Trigger on_agent_end and on_end hooks
-                    for hook in &config.run_hooks {
-                        hook.on_agent_end(&current_agent, content).await?;
-                    }
-                    for hook in &current_agent.hooks {
-                        hook.on_end(&current_agent, content).await?;
-                    }
+            self.handle_tool_calls(response.tool_calls).await?;
+        }
 
-                    // Save conversation to session if available
-                    if let Some(session) = &config.session {
-                        let assistant_msg = Message {
-                            role: "assistant".to_string(),
-                            content: content.clone(),
-                        };
-                        session
-                            .add_items(vec![
-                                serde_json::to_value(&messages[messages.len() - 1])?,
-                                serde_json::to_value(&assistant_msg)?,
-                            ])
-                            .await?;
-                    }
+        unreachable!("the run loop only ends by returning")
+    }
 
-                    return Ok(RunResult::new(content.clone()));
-                }
+    /// Check the final output and end the run with it
+    async fn finish(&mut self, output: String) -> Result<RunResult> {
+        let log = &self.guardrail_log;
+        let output = run_output_guardrails(&self.agent, &self.ctx, log, output).await?;
 
-                // Add assistant message with content
-                messages.push(Message {
-                    role: "assistant".to_string(),
-                    content: content.clone(),
-                });
-            }
+        // The transcript carries the output as modified by the guardrails
+        if let Some(Message::Assistant { content, .. }) = self.messages.last_mut() {
+            *content = Some(output.clone());
+        }
 
-            // Handle tool calls
-            if !response.tool_calls.is_empty() {
-                for tool_call in &response.tool_calls {
-                    // Trigger on_tool_start hooks
-                    for hook in &current_agent.hooks {
-                        hook.on_tool_start(&current_agent, &tool_call.name, &tool_call.arguments)
-                            .await?;
-                    }
+        // Trigger on_agent_end and on_end hooks
+        for hook in &self.config.run_hooks {
+            hook.on_agent_end(&self.ctx, &self.agent, &output).await?;
+        }
+        for hook in &self.agent.hooks {
+            hook.on_end(&self.ctx, &self.agent, &output).await?;
+        }
 
-                    // Check if it's a regular tool or a handoff
-                    let mut tool_result = None;
-                    let mut handed_off_to = None;
-
-                    // Search in agent tools
-                    if let Some(tool) = current_agent
-                        .tools
-                        .iter()
-                        .find(|t| t.name() == tool_call.name)
-                    {
-                        let result = tool
-                            .execute(tool_call.arguments.clone())
-                            .await
-                            .map_err(|e| AgentError::tool_failed(&tool_call.name, e.to_string()))?;
-                        tool_result = Some(result);
-                    } else if let Some(handoff) = current_agent
-                        .handoffs
-                        .iter()
-                        .find(|h| h.name() == tool_call.name)
-                    {
-                        let result = handoff
-                            .execute(tool_call.arguments.clone())
-                            .await
-                            .map_err(|e| AgentError::tool_failed(&tool_call.name, e.to_string()))?;
+        let usage = self.usage.lock().unwrap().clone();
+        let new_messages = self.messages.split_off(self.input_start + self.input_len);
+        let input = self.messages.split_off(self.input_start);
+
+        // Save everything that happened to the session in one write, so an
+        // interrupted run never leaves half a transcript behind
+        if let Some(session) = &self.config.session {
+            let items = input
+                .iter()
+                .chain(&new_messages)
+                .map(serde_json::to_value)
+                .collect::<serde_json::Result<Vec<_>>>()?;
+            session.add_items(items).await?;
+        }
 
-                        if let Some(_target_name) = result.get("assistant").and_then(|v| v.as_str())
-                        {
-                            handed_off_to = Some(handoff.target_agent.clone());
-                        }
-                        tool_result = Some(result);
-                    }
+        Ok(RunResult::new(output)
+            .with_guardrail_evaluations(log.evaluations())
+            .with_usage(usage)
+            .with_new_items(std::mem::take(&mut self.items))
+            .with_raw_responses(std::mem::take(&mut self.raw_responses))
+            .with_conversation(input, new_messages)
+            .with_last_agent(self.agent.clone()))
+    }
 
-                    let result = tool_result.ok_or_else(|| {
-                        AgentError::tool_failed(
-                            &tool_call.name,
-                            format!("Tool '{}' not found", tool_call.name),
-                        )
-                    })?;
-
-                    // Trigger on_tool_end hooks
-                    for hook in &current_agent.hooks {
-                        hook.on_tool_end(&current_agent, &tool_call.name, &result)
-                            .await?;
-                    }
+    /// The limit that keeps the current agent from taking turn `turn` of the
+    /// run, if any
+    fn turn_limit(&self, turn: usize) -> Option<AgentError> {
+        if turn >= self.config.max_turns {
+            return Some(AgentError::MaxTurnsExceeded(self.config.max_turns));
+        }
 
-                    // Add tool result as a message
-                    let result_str = serde_json::to_string(&result)?;
-                    messages.push(Message {
-                        role: "tool".to_string(),
-                        content: format!("Tool '{}' returned: {}", tool_call.name, result_str),
-                    });
-
-                    // If we handed off, update current agent and rebuild tools for next turn
-                    if let Some(new_agent) = handed_off_to {
-                        // Trigger on_handoff hooks
-                        for hook in &config.run_hooks {
-                            hook.on_handoff(&current_agent, &new_agent).await?;
-                        }
-                        for hook in &current_agent.hooks {
-                            hook.on_handoff(&current_agent, &new_agent).await?;
-                        }
+        let limit = self.agent.limits.max_turns?;
+        let turns = self.counts.get(&self.agent.name).map_or(0, |c| c.turns);
+        (turns >= limit).then(|| AgentError::AgentMaxTurnsExceeded {
+            agent: self.agent.name.clone(),
+            limit,
+        })
+    }
 
-                        current_agent = new_agent;
-
-                        // Synchronize system message with the new agent's instructions
-                        if !messages.is_empty() && messages[0].role == "system" {
-                            messages[0].content = current_agent.instructions.clone();
-                        } else if !current_agent.instructions.is_empty() {
-                            messages.insert(
-                                0,
-                                Message {
-                                    role: "system".to_string(),
-                                    content: current_agent.instructions.clone(),
-                                },
-                            );
-                        }
+    /// Count the tool calls requested by the current agent, returning the
+    /// limit they exceed, if any
+    fn count_tool_calls(&mut self, tool_calls: &[ToolCall]) -> Option<AgentError> {
+        let requested = tool_calls
+            .iter()
+            .filter(|call| self.find_handoff(&call.name).is_none())
+            .count();
+        let counts = self.counts.entry(self.agent.name.clone()).or_default();
+        counts.tool_calls += requested;
+
+        let limit = self.agent.limits.max_tool_calls?;
+        (counts.tool_calls > limit).then(|| AgentError::MaxToolCallsExceeded {
+            agent: self.agent.name.clone(),
+            limit,
+        })
+    }
 
-                        // Rebuild tools for the new agent
-                        let mut next_tool_definitions = Vec::new();
-                        for tool in &current_agent.tools {
-                            next_tool_definitions.push(ToolDefinition {
-                                name: tool.name().to_string(),
-                                description: tool.description().to_string(),
-                                parameters: tool.parameters_schema(),
-                            });
-                        }
-                        for handoff in &current_agent.handoffs {
-                            next_tool_definitions.push(ToolDefinition {
-                                name: handoff.name().to_string(),
-                                description: handoff.description().to_string(),
-                                parameters: handoff.parameters_schema(),
-                            });
+    /// Fail with the limit `error`, or give the agent a last turn without
+    /// tools if it asked for a best-effort answer
+    async fn final_answer(&mut self, error: AgentError) -> Result<String> {
+        if !self.agent.limits.final_answer_on_limit {
+            return Err(error);
+        }
+
+        let response = self.run_turn(Some(ToolChoice::None)).await?;
+        match response.content {
+            Some(content) if response.tool_calls.is_empty() => Ok(content),
+            _ => Err(error),
+        }
+    }
+
+    /// Make one model call, streaming its output as events
+    async fn run_turn(&mut self, tool_choice: Option<ToolChoice>) -> Result<CompletionResponse> {
+        // Trigger on_agent_start and on_start hooks
+        for hook in &self.config.run_hooks {
+            hook.on_agent_start(&self.ctx, &self.agent).await?;
+        }
+        for hook in &self.agent.hooks {
+            hook.on_start(&self.ctx, &self.agent).await?;
+        }
+
+        let agent = &self.agent;
+        let request = CompletionRequest {
+            messages: self.messages.clone(),
+            model: agent.model.clone(),
+            tools: self.tools.clone(),
+            parallel_tool_calls: Some(agent.parallel_tool_calls),
+            max_tokens: None,
+            temperature: None,
+            response_format: agent.output_schema.as_ref().map(|schema| {
+                crate::models::ResponseFormat::JsonSchema {
+                    json_schema: crate::models::JsonSchemaFormat {
+                        name: agent
+                            .output_name
+                            .clone()
+                            .unwrap_or_else(|| "output".to_string()),
+                        description: None,
+                        schema: schema.clone(),
+                        strict: Some(true),
+                    },
+                }
+            }),
+            tool_choice,
+        };
+
+        // Trigger on_llm_start hooks
+        for hook in &self.agent.hooks {
+            hook.on_llm_start(&self.ctx, &self.agent, &self.messages)
+                .await?;
+        }
+
+        // A budget can only be enforced for models with a price
+        let priced = |prices: &Arc<PriceTable>| prices.price(&self.agent.model).is_some();
+        if self.config.max_cost.is_some() && !self.config.price_table.as_ref().is_some_and(priced) {
+            return Err(AgentError::ConfigError(format!(
+                "No price for model '{}' to check the budget against",
+                self.agent.model
+            )));
+        }
+
+        let mut stream = self.model.stream(request).await?;
+        let mut content = String::new();
+        let mut pending = String::new(); // text held back for the guardrails
+        let interval = self
+            .config
+            .output_guardrail_interval
+            .filter(|_| !self.agent.output_guardrails.is_empty());
+        let mut accumulated_tool_calls: Vec<(String, String, String)> = Vec::new(); // (id, name, args)
+        let mut finish_reason = None;
+        let mut usage = None;
+
+        // Stream chunks and emit events
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+
+            // Emit text deltas as raw response events
+            if let Some(delta) = chunk.delta {
+                content.push_str(&delta);
+                match interval {
+                    Some(interval) => {
+                        pending.push_str(&delta);
+                        if let Some(len) = interval.ready_len(&pending) {
+                            let checked = content.len() - pending.len() + len;
+                            self.check_streamed_output(&content[..checked]).await?;
+                            let data = pending.drain(..len).collect();
+                            self.emit(StreamEvent::RawResponse(RawResponseEvent { data }));
                         }
-                        current_tools = if !next_tool_definitions.is_empty() {
-                            Some(next_tool_definitions)
-                        } else {
-                            None
-                        };
                     }
+                    None => self.emit(StreamEvent::RawResponse(RawResponseEvent { data: delta })),
                 }
             }
 
-            // Check if we've exceeded max turns
-            if turn >= config.max_turns - 1 {
-                return Err(AgentError::MaxTurnsExceeded(config.max_turns));
+            // Accumulate tool call deltas
+            for tc_delta in chunk.tool_call_deltas {
+                // Ensure we have enough space in the vector
+                while accumulated_tool_calls.len() <= tc_delta.index {
+                    accumulated_tool_calls.push((String::new(), String::new(), String::new()));
+                }
+
+                let (id, name, args) = &mut accumulated_tool_calls[tc_delta.index];
+                if let Some(delta_id) = tc_delta.id {
+                    id.push_str(&delta_id);
+                }
+                if let Some(delta_name) = tc_delta.name {
+                    name.push_str(&delta_name);
+                }
+                if let Some(delta_args) = tc_delta.arguments {
+                    args.push_str(&delta_args);
+                }
+            }
+
+            if chunk.finish_reason.is_some() {
+                finish_reason = chunk.finish_reason;
+            }
+            if chunk.usage.is_some() {
+                usage = chunk.usage;
             }
         }
 
-        Err(AgentError::MaxTurnsExceeded(config.max_turns))
-    }
+        // Release the rest of the text once it has been checked
+        if !pending.is_empty() {
+            self.check_streamed_output(&content).await?;
+            self.emit(StreamEvent::RawResponse(RawResponseEvent { data: pending }));
+        }
 
-    /// Run an agent with streaming enabled
-    ///
-    /// # Example
-    ///
-    /// ```rust,no_run
-    /// use openai_agents::{Agent, Runner};
-    /// use futures::StreamExt;
-    ///
-    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// let agent = Agent::builder("Assistant")
-    ///     .instructions("You are helpful.")
-    ///     .build();
-    ///
-    /// let result = Runner::run_streamed(&agent, "Hello!").await?;
-    ///
-    /// // Stream events
-    /// let mut stream = result.stream_events();
-    /// while let Some(event) = stream.next().await {
-    ///     // Process event
-    /// }
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub async fn run_streamed(
-        agent: &Agent,
-        input: impl Into<String>,
-    ) -> Result<crate::streaming::StreamedRunResult> {
-        Self::run_streamed_with_config(agent, input, RunConfig::default()).await
+        let response = CompletionResponse {
+            content: (!content.is_empty()).then_some(content),
+            tool_calls: accumulated_tool_calls
+                .into_iter()
+                .filter(|(_, name, _)| !name.is_empty())
+                .map(|(id, name, args)| ToolCall {
+                    id,
+                    name,
+                    arguments: serde_json::from_str(&args).unwrap_or(serde_json::json!({})),
+                })
+                .collect(),
+            finish_reason,
+            usage,
+        };
+
+        self.counts
+            .entry(self.agent.name.clone())
+            .or_default()
+            .turns += 1;
+        self.record_usage(&response)?;
+        self.check_token_limits()?;
+
+        self.raw_responses.push(response.clone());
+
+        // Trigger on_llm_end hooks
+        for hook in &self.agent.hooks {
+            hook.on_llm_end(&self.ctx, &self.agent, &response).await?;
+        }
+
+        if let Some(content) = &response.content {
+            self.emit_item(
+                RunItemEventName::MessageOutputCreated,
+                RunItem::MessageOutput {
+                    content: content.clone(),
+                },
+            );
+        }
+
+        // Record the reply, with the tool calls that the results will refer to
+        self.messages.push(Message::assistant_tool_calls(
+            response.content.clone(),
+            response.tool_calls.clone(),
+        ));
+
+        Ok(response)
     }
 
-    /// Run an agent with streaming and custom configuration
-    pub async fn run_streamed_with_config(
-        agent: &Agent,
-        input: impl Into<String>,
-        config: RunConfig,
-    ) -> Result<crate::streaming::StreamedRunResult> {
-        use crate::stream_events::{
-            RawResponseEvent, RunItem, RunItemEventName, RunItemStreamEvent, StreamEvent,
-        };
-        use tokio::sync::mpsc;
+    /// Execute the tool calls of a turn and follow a handoff, if any
+    async fn handle_tool_calls(&mut self, tool_calls: Vec<ToolCall>) -> Result<()> {
+        // Emit tool call events
+        for tool_call in &tool_calls {
+            let (name, item) = match self.find_handoff(&tool_call.name) {
+                Some(handoff) => (
+                    RunItemEventName::HandoffRequested,
+                    RunItem::HandoffRequested {
+                        agent_name: handoff.target_agent.name.clone(),
+                    },
+                ),
+                None => (
+                    RunItemEventName::ToolCalled,
+                    RunItem::ToolCall {
+                        name: tool_call.name.clone(),
+                        arguments: tool_call.arguments.clone(),
+                    },
+                ),
+            };
+            self.emit_item(name, item);
+        }
 
-        let (tx, rx) = mpsc::unbounded_channel();
-        let (streamed_result, shared_state) = crate::streaming::StreamedRunResult::new(rx);
-        let input = input.into();
-
-        // Initialize model provider
-        let model: Arc<dyn ModelProvider> = if let Some(m) = config.model_override.clone() {
-            m
-        } else {
-            Arc::new(OpenAIResponsesModel::new())
-        };
+        let outcomes = execute_tool_calls(
+            &self.agent,
+            &self.ctx,
+            &self.config,
+            &self.guardrail_log,
+            &tool_calls,
+        )
+        .await?;
+
+        for (tool_call, outcome) in tool_calls.iter().zip(outcomes) {
+            let ToolCallOutcome { output, handoff } = outcome;
+            let output = serde_json::to_string(&output)?;
+
+            // Add tool result as a message
+            self.messages
+                .push(Message::tool(&tool_call.id, output.clone()));
+
+            match handoff {
+                Some(new_agent) => self.handoff(new_agent).await?,
+                None => self.emit_item(
+                    RunItemEventName::ToolOutput,
+                    RunItem::ToolOutput {
+                        name: tool_call.name.clone(),
+                        output,
+                    },
+                ),
+            }
+        }
 
-        // Spawn a task to run the agent and emit events
-        let agent = agent.clone();
-        let shared_state_bg = shared_state.clone();
-        tokio::spawn(async move {
-            let mut messages = Vec::new();
-            let mut final_output = String::new();
-
-            // Add system message if agent has instructions
-            if !agent.instructions.is_empty() {
-                messages.push(Message {
-                    role: "system".to_string(),
-                    content: agent.instructions.clone(),
-                });
+        Ok(())
+    }
+
+    /// Switch the run over to `new_agent`
+    async fn handoff(&mut self, new_agent: Arc<Agent>) -> Result<()> {
+        // Trigger on_handoff hooks
+        for hook in &self.config.run_hooks {
+            hook.on_handoff(&self.ctx, &self.agent, &new_agent).await?;
+        }
+        for hook in &self.agent.hooks {
+            hook.on_handoff(&self.ctx, &self.agent, &new_agent).await?;
+        }
+
+        self.emit_item(
+            RunItemEventName::HandoffOccurred,
+            RunItem::HandoffOccurred {
+                agent_name: new_agent.name.clone(),
+            },
+        );
+        self.emit(StreamEvent::AgentUpdated(AgentUpdatedEvent {
+            new_agent: new_agent.clone(),
+        }));
+
+        self.ctx = self.ctx.for_agent(&new_agent.name);
+        self.tools = tool_definitions(&new_agent);
+        self.agent = new_agent;
+
+        // Synchronize system message with the new agent's instructions,
+        // leaving system messages from the history or the input alone
+        if self.instructions_message {
+            if let Some(Message::System { content }) = self.messages.first_mut() {
+                *content = self.agent.instructions.clone();
             }
+        } else if !self.agent.instructions.is_empty() {
+            self.messages
+                .insert(0, Message::system(self.agent.instructions.clone()));
+            self.input_start += 1;
+            self.instructions_message = true;
+        }
 
-            // Load session history if available
-            if let Some(session) = &config.session {
-                if let Ok(history) = session.get_items(None).await {
-                    for item in history {
-                        if let Ok(msg) = serde_json::from_value::<Message>(item) {
-                            messages.push(msg);
-                        }
-                    }
-                }
+        Ok(())
+    }
+
+    /// Check streamed text against the output guardrails, ignoring `Modify`
+    async fn check_streamed_output(&self, text: &str) -> Result<()> {
+        for guardrail in &self.agent.output_guardrails {
+            let output = guardrail.check(&self.ctx, text).await?;
+            let evaluation = self.guardrail_log.record(
+                GuardrailKind::Output,
+                guardrail.name(),
+                &self.agent,
+                None,
+                output,
+            );
+            if let GuardrailResult::Block { reason } = evaluation.result.clone() {
+                return Err(evaluation.into_error(reason));
             }
+        }
+
+        Ok(())
+    }
+
+    /// Account the usage and cost of a model call, and enforce the budget
+    fn record_usage(&self, response: &CompletionResponse) -> Result<()> {
+        // Count the call even if the provider reported no tokens
+        let usage = Usage {
+            requests: 1,
+            ..response.usage.unwrap_or_default()
+        };
+        let model = &self.agent.model;
+        let cost = self
+            .config
+            .price_table
+            .as_ref()
+            .and_then(|prices| prices.cost(model, &usage));
+
+        // Enforce the budget on the estimated spend including this call
+        let mut run_usage = self.usage.lock().unwrap();
+        run_usage.record(ModelCall {
+            agent_name: self.agent.name.clone(),
+            model: model.clone(),
+            usage,
+            cost,
+        });
+        let spent = run_usage.cost().unwrap_or_default();
+        match self.config.max_cost {
+            Some(limit) if spent > limit => Err(AgentError::BudgetExceeded { limit, spent }),
+            _ => Ok(()),
+        }
+    }
 
-            // Add user input
-            messages.push(Message {
-                role: "user".to_string(),
-                content: input.to_string(),
-            });
-
-            // Initial current agent and tools
-            let mut current_agent = Arc::new(agent.clone());
-
-            // Convert agent tools and handoffs to tool definitions
-            let mut tool_definitions = Vec::new();
-            for tool in &current_agent.tools {
-                tool_definitions.push(ToolDefinition {
-                    name: tool.name().to_string(),
-                    description: tool.description().to_string(),
-                    parameters: tool.parameters_schema(),
+    /// Fail if the current agent used more tokens than it may
+    fn check_token_limits(&self) -> Result<()> {
+        let limits = &self.agent.limits;
+        let usage = self.usage.lock().unwrap().by_agent[&self.agent.name];
+
+        if let Some(limit) = limits.max_output_tokens {
+            if usage.completion_tokens > limit {
+                return Err(AgentError::MaxOutputTokensExceeded {
+                    agent: self.agent.name.clone(),
+                    limit,
+                    used: usage.completion_tokens,
                 });
             }
-            for handoff in &current_agent.handoffs {
-                tool_definitions.push(ToolDefinition {
-                    name: handoff.name().to_string(),
-                    description: handoff.description().to_string(),
-                    parameters: handoff.parameters_schema(),
+        }
+        if let Some(limit) = limits.max_total_tokens {
+            if usage.total_tokens > limit {
+                return Err(AgentError::MaxTotalTokensExceeded {
+                    agent: self.agent.name.clone(),
+                    limit,
+                    used: usage.total_tokens,
                 });
             }
+        }
 
-            let mut current_tools = if !tool_definitions.is_empty() {
-                Some(tool_definitions)
-            } else {
-                None
-            };
+        Ok(())
+    }
 
-            // Main agent loop with streaming
-            for _turn in 0..config.max_turns {
-                // Trigger on_agent_start and on_start hooks
-                for hook in &config.run_hooks {
-                    let _ = hook.on_agent_start(&current_agent).await;
-                }
-                for hook in &current_agent.hooks {
-                    let _ = hook.on_start(&current_agent).await;
-                }
+    /// Emit a [`StreamEvent::GuardrailTripped`] event if `error` is a guardrail error
+    fn tripped(&self, error: AgentError) -> AgentError {
+        if let AgentError::InputGuardrailTriggered {
+            guardrail, reason, ..
+        }
+        | AgentError::OutputGuardrailTriggered {
+            guardrail, reason, ..
+        }
+        | AgentError::ToolInputGuardrailTriggered {
+            guardrail, reason, ..
+        }
+        | AgentError::ToolOutputGuardrailTriggered {
+            guardrail, reason, ..
+        } = &error
+        {
+            self.emit(StreamEvent::GuardrailTripped(GuardrailTrippedEvent {
+                guardrail: guardrail.clone(),
+                reason: reason.clone(),
+            }));
+        }
+        error
+    }
 
-                let request = CompletionRequest {
-                    messages: messages.clone(),
-                    model: current_agent.model.clone(),
-                    tools: current_tools.clone(),
-                    max_tokens: None,
-                    temperature: None,
-                    response_format: current_agent.output_schema.as_ref().map(|schema| {
-                        crate::models::ResponseFormat::JsonSchema {
-                            json_schema: crate::models::JsonSchemaFormat {
-                                name: current_agent
-                                    .output_name
-                                    .clone()
-                                    .unwrap_or_else(|| "output".to_string()),
-                                description: None,
-                                schema: schema.clone(),
-                                strict: Some(true),
-                            },
-                        }
-                    }),
-                };
+    fn find_handoff(&self, name: &str) -> Option<&Handoff> {
+        self.agent.handoffs.iter().find(|h| h.name() == name)
+    }
 
-                // Trigger on_llm_start hooks
-                for hook in &current_agent.hooks {
-                    let _ = hook.on_llm_start(&current_agent, &messages).await;
-                }
+    /// Send an event to the stream, unless nobody is listening anymore
+    fn emit(&self, event: StreamEvent) {
+        let _ = self.events.send(event);
+    }
 
-                // Use actual streaming from model provider
-                let mut stream = match model.stream(request).await {
-                    Ok(s) => s,
-                    Err(e) => {
-                        let _ = tx.send(StreamEvent::RawResponse(RawResponseEvent {
-                            data: format!("Error: {}", e),
-                        }));
-                        break;
-                    }
-                };
+    /// Record an item of the run and send it to the stream
+    fn emit_item(&mut self, name: RunItemEventName, item: RunItem) {
+        self.items.push(item.clone());
+        self.emit(StreamEvent::RunItem(RunItemStreamEvent { name, item }));
+    }
+}
 
-                let mut accumulated_content = String::new();
-                let mut accumulated_tool_calls: Vec<(String, String, String)> = Vec::new(); // (id, name, args)
-
-                // Stream chunks and emit events
-                while let Some(chunk_result) = stream.next().await {
-                    let chunk = match chunk_result {
-                        Ok(c) => c,
-                        Err(_) => break,
-                    };
-
-                    // Emit text deltas as raw response events
-                    if let Some(delta) = &chunk.delta {
-                        accumulated_content.push_str(delta);
-                        let _ = tx.send(StreamEvent::RawResponse(RawResponseEvent {
-                            data: delta.to_string(),
-                        }));
-                    }
+/// Content of the last user message of `messages`, if any
+fn last_user_content(messages: &mut [Message]) -> Option<&mut String> {
+    messages.iter_mut().rev().find_map(|message| match message {
+        Message::User { content } => Some(content),
+        _ => None,
+    })
+}
 
-                    // Accumulate tool call deltas
-                    for tc_delta in &chunk.tool_call_deltas {
-                        // Ensure we have enough space in the vector
-                        while accumulated_tool_calls.len() <= tc_delta.index {
-                            accumulated_tool_calls.push((
-                                String::new(),
-                                String::new(),
-                                String::new(),
-                            ));
-                        }
+/// Tool definitions for the tools and handoffs of `agent`
+fn tool_definitions(agent: &Agent) -> Option<Vec<ToolDefinition>> {
+    let definitions: Vec<_> = agent
+        .tools
+        .iter()
+        .map(|tool| ToolDefinition {
+            name: tool.name().to_string(),
+            description: tool.description().to_string(),
+            parameters: tool.parameters_schema(),
+        })
+        .chain(agent.handoffs.iter().map(|handoff| ToolDefinition {
+            name: handoff.name().to_string(),
+            description: handoff.description().to_string(),
+            parameters: handoff.parameters_schema(),
+        }))
+        .collect();
+
+    (!definitions.is_empty()).then_some(definitions)
+}
 
-                        let (id, name, args) = &mut accumulated_tool_calls[tc_delta.index];
-                        if let Some(ref delta_id) = tc_delta.id {
-                            id.push_str(delta_id);
-                        }
-                        if let Some(ref delta_name) = tc_delta.name {
-                            name.push_str(delta_name);
-                        }
-                        if let Some(ref delta_args) = tc_delta.arguments {
-                            args.push_str(delta_args);
-                        }
-                    }
+/// Evaluations of the guardrails of a run, shared with concurrent tool calls
+#[derive(Clone, Default)]
+struct GuardrailLog(Arc<Mutex<Vec<GuardrailEvaluation>>>);
 
-                    // Check for finish
-                    if chunk.finish_reason.is_some() {
-                        break;
-                    }
-                }
+impl GuardrailLog {
+    /// Record the output of a guardrail check
+    fn record(
+        &self,
+        kind: GuardrailKind,
+        guardrail: &str,
+        agent: &Agent,
+        tool_name: Option<&str>,
+        output: GuardrailOutput,
+    ) -> GuardrailEvaluation {
+        let evaluation = GuardrailEvaluation {
+            guardrail: guardrail.to_string(),
+            kind,
+            agent_name: agent.name.clone(),
+            tool_name: tool_name.map(str::to_string),
+            result: output.result,
+            category: output.category,
+            output_info: output.output_info,
+        };
+        self.0.lock().unwrap().push(evaluation.clone());
+        evaluation
+    }
 
-                // Add assistant message if we have content
-                if !accumulated_content.is_empty() {
-                    final_output = accumulated_content.clone();
-                    messages.push(Message {
-                        role: "assistant".to_string(),
-                        content: accumulated_content.clone(),
-                    });
-
-                    // Emit message output event
-                    let _ = tx.send(StreamEvent::RunItem(RunItemStreamEvent {
-                        name: RunItemEventName::MessageOutputCreated,
-                        item: RunItem::MessageOutput {
-                            content: accumulated_content.clone(),
-                        },
-                    }));
-                }
+    fn evaluations(&self) -> Vec<GuardrailEvaluation> {
+        self.0.lock().unwrap().clone()
+    }
+}
 
-                // Handle tool calls
-                if !accumulated_tool_calls.is_empty() {
-                    let mut handed_off = false;
-                    for (_id, name, args_str) in accumulated_tool_calls {
-                        if name.is_empty() {
-                            continue;
-                        }
+/// Run the input guardrails of `agent` that do (or don't) run in parallel,
+/// returning the input as modified by them
+async fn run_input_guardrails(
+    agent: &Agent,
+    ctx: &RunContext,
+    log: &GuardrailLog,
+    mut input: String,
+    parallel: bool,
+) -> Result<String> {
+    for guardrail in agent
+        .input_guardrails
+        .iter()
+        .filter(|g| g.run_in_parallel() == parallel)
+    {
+        let output = guardrail.check(ctx, &input).await?;
+        let evaluation = log.record(GuardrailKind::Input, guardrail.name(), agent, None, output);
+        match evaluation.result.clone() {
+            GuardrailResult::Allow => {}
+            GuardrailResult::Block { reason } => return Err(evaluation.into_error(reason)),
+            GuardrailResult::Modify { .. } if parallel => {
+                return Err(AgentError::ConfigError(format!(
+                    "Input guardrail '{}' runs in parallel and cannot modify the input",
+                    guardrail.name()
+                )));
+            }
+            GuardrailResult::Modify { new_content } => input = new_content,
+        }
+    }
 
-                        let arguments =
-                            serde_json::from_str(&args_str).unwrap_or(serde_json::json!({}));
+    Ok(input)
+}
 
-                        // Trigger on_tool_start hooks
-                        for hook in &current_agent.hooks {
-                            let _ = hook.on_tool_start(&current_agent, &name, &arguments).await;
-                        }
+/// Run the output guardrails of `agent`, returning the output as modified by them
+async fn run_output_guardrails(
+    agent: &Agent,
+    ctx: &RunContext,
+    log: &GuardrailLog,
+    mut output: String,
+) -> Result<String> {
+    for guardrail in &agent.output_guardrails {
+        let guardrail_output = guardrail.check(ctx, &output).await?;
+        let evaluation = log.record(
+            GuardrailKind::Output,
+            guardrail.name(),
+            agent,
+            None,
+            guardrail_output,
+        );
+        match evaluation.result.clone() {
+            GuardrailResult::Allow => {}
+            GuardrailResult::Block { reason } => return Err(evaluation.into_error(reason)),
+            GuardrailResult::Modify { new_content } => output = new_content,
+        }
+    }
 
-                        // Emit tool call event
-                        let _ = tx.send(StreamEvent::RunItem(RunItemStreamEvent {
-                            name: RunItemEventName::ToolCalled,
-                            item: RunItem::ToolCall {
-                                name: name.clone(),
-                                arguments: arguments.clone(),
-                            },
-                        }));
-
-                        let mut tool_result = None;
-                        let mut handed_off_to = None;
-
-                        // Search in agent tools
-                        if let Some(tool) = current_agent.tools.iter().find(|t| t.name() == name) {
-                            if let Ok(result) = tool.execute(arguments.clone()).await {
-                                tool_result = Some(result);
-                            }
-                        } else if let Some(handoff) =
-                            current_agent.handoffs.iter().find(|h| h.name() == name)
-                        {
-                            if let Ok(result) = handoff.execute(arguments.clone()).await {
-                                if let Some(_target_name) =
-                                    result.get("assistant").and_then(|v| v.as_str())
-                                {
-                                    handed_off_to = Some(handoff.target_agent.clone());
-                                }
-                                tool_result = Some(result);
-                            }
-                        }
+    Ok(output)
+}
 
-                        if let Some(result) = tool_result {
-                            // Trigger on_tool_end hooks
-                            for hook in &current_agent.hooks {
-                                let _ = hook.on_tool_end(&current_agent, &name, &result).await;
-                            }
-
-                            let result_str = serde_json::to_string(&result).unwrap_or_default();
-
-                            // Emit tool output event
-                            let _ = tx.send(StreamEvent::RunItem(RunItemStreamEvent {
-                                name: RunItemEventName::ToolOutput,
-                                item: RunItem::ToolOutput {
-                                    name: name.clone(),
-                                    output: result_str.clone(),
-                                },
-                            }));
-
-                            // Add tool result as a message
-                            messages.push(Message {
-                                role: "tool".to_string(),
-                                content: format!("Tool '{}' returned: {}", name, result_str),
-                            });
-
-                            // If we handed off, update current agent and rebuild tools
-                            if let Some(new_agent) = handed_off_to {
-                                // Trigger on_handoff hooks
-                                for hook in &config.run_hooks {
-                                    let _ = hook.on_handoff(&current_agent, &new_agent).await;
-                                }
-                                for hook in &current_agent.hooks {
-                                    let _ = hook.on_handoff(&current_agent, &new_agent).await;
-                                }
-
-                                current_agent = new_agent;
-                                handed_off = true;
-
-                                // Emit agent updated event
-                                use crate::stream_events::AgentUpdatedEvent;
-                                let _ = tx.send(StreamEvent::AgentUpdated(AgentUpdatedEvent {
-                                    new_agent: (*current_agent).clone(),
-                                }));
-
-                                // Synchronize system message
-                                if !messages.is_empty() && messages[0].role == "system" {
-                                    messages[0].content = current_agent.instructions.clone();
-                                } else if !current_agent.instructions.is_empty() {
-                                    messages.insert(
-                                        0,
-                                        Message {
-                                            role: "system".to_string(),
-                                            content: current_agent.instructions.clone(),
-                                        },
-                                    );
-                                }
-
-                                // Rebuild tools
-                                let mut next_tool_definitions = Vec::new();
-                                for tool in &current_agent.tools {
-                                    next_tool_definitions.push(ToolDefinition {
-                                        name: tool.name().to_string(),
-                                        description: tool.description().to_string(),
-                                        parameters: tool.parameters_schema(),
-                                    });
-                                }
-                                for handoff in &current_agent.handoffs {
-                                    next_tool_definitions.push(ToolDefinition {
-                                        name: handoff.name().to_string(),
-                                        description: handoff.description().to_string(),
-                                        parameters: handoff.parameters_schema(),
-                                    });
-                                }
-                                current_tools = if !next_tool_definitions.is_empty() {
-                                    Some(next_tool_definitions)
-                                } else {
-                                    None
-                                };
-                            }
-                        }
-                    }
+/// Result of executing a single tool call
+struct ToolCallOutcome {
+    /// Output sent back to the model
+    output: Value,
+    /// Agent to hand off to, if the call was a handoff
+    handoff: Option<Arc<Agent>>,
+}
 
-                    if !handed_off {
-                        // If we had tool calls but no handoff, and the model might want to say more,
-                        // we let the loop continue to the next OpenAI turn.
-                    }
-                } else {
-                    // Trigger on_agent_end and on_end hooks
-                    for hook in &config.run_hooks {
-                        let _ = hook
-                            .on_agent_end(&current_agent, &accumulated_content)
-                            .await;
-                    }
-                    for hook in &current_agent.hooks {
-                        let _ = hook.on_end(&current_agent, &accumulated_content).await;
-                    }
+/// Execute the tool calls of one turn, returning their outcomes in call order
+///
+/// Calls run concurrently (up to `RunConfig::max_concurrent_tool_calls`) when
+/// the agent allows parallel tool calls, and one after another otherwise.
+async fn execute_tool_calls(
+    agent: &Agent,
+    ctx: &RunContext,
+    config: &RunConfig,
+    log: &GuardrailLog,
+    tool_calls: &[ToolCall],
+) -> Result<Vec<ToolCallOutcome>> {
+    let concurrency = if agent.parallel_tool_calls {
+        config
+            .max_concurrent_tool_calls
+            .unwrap_or(tool_calls.len())
+            .max(1)
+    } else {
+        1
+    };
+
+    // Boxing keeps the futures `Send` for the spawned streaming task
+    let calls: Vec<_> = tool_calls
+        .iter()
+        .map(|tool_call| execute_tool_call(agent, ctx, config, log, tool_call).boxed())
+        .collect();
+
+    futures::stream::iter(calls)
+        .buffered(concurrency)
+        .try_collect()
+        .await
+}
+
+/// Execute `tool`, checking the call against the tool guardrails of the run,
+/// the agent and the tool
+///
+/// The outer error aborts the run, while the inner result is subject to the
+/// tool error policy.
+async fn execute_guarded(
+    tool: &dyn Tool,
+    agent: &Agent,
+    ctx: &RunContext,
+    config: &RunConfig,
+    log: &GuardrailLog,
+    tool_call: &ToolCall,
+) -> Result<Result<Value>> {
+    let mut arguments = tool_call.arguments.clone();
+    let input_guardrails = config
+        .tool_input_guardrails
+        .iter()
+        .chain(&agent.tool_input_guardrails)
+        .chain(tool.input_guardrails());
+    for guardrail in input_guardrails {
+        let output = guardrail.check(ctx, &tool_call.name, &arguments).await?;
+        let evaluation = log.record(
+            GuardrailKind::ToolInput,
+            guardrail.name(),
+            agent,
+            Some(&tool_call.name),
+            output,
+        );
+        match evaluation.result.clone() {
+            GuardrailResult::Allow => {}
+            GuardrailResult::Modify { new_content } => {
+                arguments = serde_json::from_str(&new_content).map_err(|e| {
+                    AgentError::ConfigError(format!(
+                        "Tool input guardrail '{}' produced invalid arguments: {}",
+                        guardrail.name(),
+                        e
+                    ))
+                })?;
+            }
+            GuardrailResult::Block { reason } => {
+                return match guardrail.block_mode() {
+                    BlockMode::Abort => Err(evaluation.into_error(reason)),
+                    BlockMode::RejectContent => Ok(Ok(refusal(guardrail.name(), &reason))),
+                };
+            }
+        }
+    }
+
+    let execution = tool.execute(ctx, arguments);
+    let result = match tool.timeout().or(config.tool_timeout) {
+        // Dropping the execution future on timeout cancels the tool
+        Some(timeout) => tokio::time::timeout(timeout, execution)
+            .await
+            .unwrap_or_else(|_| {
+                Err(AgentError::ToolTimeout(format!(
+                    "Tool '{}' timed out after {}ms",
+                    tool_call.name,
+                    timeout.as_millis()
+                )))
+            }),
+        None => execution.await,
+    };
+    let mut output = match result {
+        Ok(output) => output,
+        Err(error) => return Ok(Err(error)),
+    };
+
+    let output_guardrails = config
+        .tool_output_guardrails
+        .iter()
+        .chain(&agent.tool_output_guardrails)
+        .chain(tool.output_guardrails());
+    for guardrail in output_guardrails {
+        let guardrail_output = guardrail.check(ctx, &tool_call.name, &output).await?;
+        let evaluation = log.record(
+            GuardrailKind::ToolOutput,
+            guardrail.name(),
+            agent,
+            Some(&tool_call.name),
+            guardrail_output,
+        );
+        match evaluation.result.clone() {
+            GuardrailResult::Allow => {}
+            GuardrailResult::Modify { new_content } => {
+                output = serde_json::from_str(&new_content).unwrap_or(Value::String(new_content));
+            }
+            GuardrailResult::Block { reason } => {
+                return match guardrail.block_mode() {
+                    BlockMode::Abort => Err(evaluation.into_error(reason)),
+                    BlockMode::RejectContent => Ok(Ok(refusal(guardrail.name(), &reason))),
+                };
+            }
+        }
+    }
+
+    Ok(Ok(output))
+}
+
+/// Tool result telling the model that a guardrail rejected the call
+fn refusal(guardrail: &str, reason: &str) -> Value {
+    Value::String(format!(
+        "The tool call was rejected by guardrail '{}': {}",
+        guardrail, reason
+    ))
+}
+
+/// Execute one tool call of `agent`, applying the tool error policy to failures
+async fn execute_tool_call(
+    agent: &Agent,
+    ctx: &RunContext,
+    config: &RunConfig,
+    log: &GuardrailLog,
+    tool_call: &ToolCall,
+) -> Result<ToolCallOutcome> {
+    let tool_ctx = ctx.for_tool_call(&tool_call.id);
+
+    // Trigger on_tool_start hooks
+    for hook in &agent.hooks {
+        hook.on_tool_start(&tool_ctx, agent, &tool_call.name, &tool_call.arguments)
+            .await?;
+    }
 
-                    // No tool calls and we have content (or already handled content)
-                    // This is a final response
-                    break;
+    let mut handoff_target = None;
+
+    // Check if it's a regular tool or a handoff
+    let result = if let Some(tool) = agent.tools.iter().find(|t| t.name() == tool_call.name) {
+        execute_guarded(tool.as_ref(), agent, &tool_ctx, config, log, tool_call).await?
+    } else if let Some(handoff) = agent.handoffs.iter().find(|h| h.name() == tool_call.name) {
+        let result = handoff
+            .execute(&tool_ctx, tool_call.arguments.clone())
+            .await;
+        if let Ok(output) = &result {
+            if output.get("assistant").and_then(|v| v.as_str()).is_some() {
+                handoff_target = Some(handoff.target_agent.clone());
+            }
+        }
+        result
+    } else {
+        Err(AgentError::tool_failed(
+            &tool_call.name,
+            format!("Tool '{}' not found", tool_call.name),
+        ))
+    };
+
+    let output = match result {
+        Ok(output) => output,
+        Err(error) => {
+            let error = match error {
+                error @ (AgentError::ToolExecutionFailed { .. } | AgentError::ToolTimeout(_)) => {
+                    error
                 }
+                error => AgentError::tool_failed(&tool_call.name, error.to_string()),
+            };
+
+            // Trigger on_tool_error hooks
+            for hook in &agent.hooks {
+                hook.on_tool_error(&tool_ctx, agent, &tool_call.name, &error)
+                    .await?;
             }
 
-            // Set final result
-            let mut state = shared_state_bg.lock().unwrap();
-            state.final_result = Some(RunResult::new(final_output));
-        });
+            agent
+                .tool_error_policy
+                .as_ref()
+                .unwrap_or(&config.tool_error_policy)
+                .handle(&tool_call.name, error)?
+        }
+    };
 
-        Ok(streamed_result)
+    // Trigger on_tool_end hooks
+    for hook in &agent.hooks {
+        hook.on_tool_end(&tool_ctx, agent, &tool_call.name, &output)
+            .await?;
     }
+
+    Ok(ToolCallOutcome {
+        output,
+        handoff: handoff_target,
+    })
 }
@@ -0,0 +1,114 @@
+//! JSON schema helpers for tool parameters and structured outputs
+
+use schemars::{JsonSchema, generate::SchemaSettings};
+use serde_json::Value;
+
+/// Generate a JSON schema for `T` that is valid for OpenAI strict mode
+///
+/// Subschemas are inlined, every object gets `additionalProperties: false`
+/// and all of its properties are listed as required. `Option<T>` fields stay
+/// nullable, which is how strict mode expresses optional values.
+pub fn strict_schema_for<T: JsonSchema + ?Sized>() -> Value {
+    let generator = SchemaSettings::draft2020_12()
+        .with(|settings| settings.inline_subschemas = true)
+        .into_generator();
+    let mut schema = generator.into_root_schema_for::<T>().to_value();
+
+    if let Some(obj) = schema.as_object_mut() {
+        obj.remove("$schema");
+        obj.remove("title");
+    }
+    ensure_strict(&mut schema);
+    remove_unsupported_formats(&mut schema);
+
+    schema
+}
+
+/// String formats accepted by strict mode; anything else (e.g. schemars'
+/// `uint32`) is rejected by the API
+const SUPPORTED_FORMATS: &[&str] = &[
+    "date-time",
+    "time",
+    "date",
+    "duration",
+    "email",
+    "hostname",
+    "ipv4",
+    "ipv6",
+    "uuid",
+];
+
+fn remove_unsupported_formats(value: &mut Value) {
+    if let Some(obj) = value.as_object_mut() {
+        if obj
+            .get("format")
+            .and_then(Value::as_str)
+            .is_some_and(|format| !SUPPORTED_FORMATS.contains(&format))
+        {
+            obj.remove("format");
+        }
+        for (_, v) in obj.iter_mut() {
+            remove_unsupported_formats(v);
+        }
+    } else if let Some(arr) = value.as_array_mut() {
+        for v in arr.iter_mut() {
+            remove_unsupported_formats(v);
+        }
+    }
+}
+
+/// Recursively set `additionalProperties: false` on every object schema
+///
+/// Nullable objects (`"type": ["object", "null"]`, as schemars gives
+/// `Option<T>`) and schemas with `properties` count as object schemas too.
+pub fn ensure_no_additional_properties(value: &mut Value) {
+    if let Some(obj) = value.as_object_mut() {
+        if is_object_schema(obj) {
+            obj.insert("additionalProperties".to_string(), Value::Bool(false));
+        }
+        for (_, v) in obj.iter_mut() {
+            ensure_no_additional_properties(v);
+        }
+    } else if let Some(arr) = value.as_array_mut() {
+        for v in arr.iter_mut() {
+            ensure_no_additional_properties(v);
+        }
+    }
+}
+
+fn is_object_schema(schema: &serde_json::Map<String, Value>) -> bool {
+    let object_type = match schema.get("type") {
+        Some(Value::String(ty)) => ty == "object",
+        Some(Value::Array(types)) => types.iter().any(|ty| ty == "object"),
+        _ => false,
+    };
+    object_type || schema.get("properties").is_some_and(Value::is_object)
+}
+
+/// Recursively make every object schema strict
+///
+/// This is [`ensure_no_additional_properties`] plus marking every declared
+/// property as required, as the strict function calling mode demands.
+pub fn ensure_strict(value: &mut Value) {
+    ensure_no_additional_properties(value);
+    mark_all_required(value);
+}
+
+fn mark_all_required(value: &mut Value) {
+    if let Some(obj) = value.as_object_mut() {
+        if let Some(Value::Object(properties)) = obj.get("properties") {
+            let required = properties
+                .keys()
+                .map(|k| Value::String(k.clone()))
+                .collect();
+            obj.insert("required".to_string(), Value::Array(required));
+        }
+        for (_, v) in obj.iter_mut() {
+            mark_all_required(v);
+        }
+    } else if let Some(arr) = value.as_array_mut() {
+        for v in arr.iter_mut() {
+            mark_all_required(v);
+        }
+    }
+}
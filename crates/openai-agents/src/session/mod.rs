@@ -19,24 +19,54 @@ pub trait Session: Send + Sync {
 
     /// Clear all items from the session
     async fn clear_session(&self) -> Result<()>;
-}
 
-/// Session settings
-#[derive(Debug, Clone, Default)]
-pub struct SessionSettings {
-    /// Maximum number of items to keep in session
-    pub max_items: Option<usize>,
+    /// Replace all items of the session with `items`
+    ///
+    /// The default clears the session and then adds the items, so a failure
+    /// in between loses the history; the built-in sessions replace the items
+    /// atomically.
+    async fn replace_items(&self, items: Vec<Value>) -> Result<()> {
+        self.clear_session().await?;
+        self.add_items(items).await
+    }
+
+    /// Settings applied when a run loads the history, unless the run
+    /// overrides them
+    fn settings(&self) -> Option<&SessionSettings> {
+        None
+    }
+
+    /// Get the history to send to the model, trimmed to `settings`
+    async fn get_history(&self, settings: &SessionSettings) -> Result<Vec<Value>> {
+        let items = self.get_items(settings.fetch_limit()).await?;
+        Ok(settings.trim(items))
+    }
 }
 
+mod settings;
+
+pub use settings::{SessionSettings, estimate_tokens};
+
+pub mod summarizing;
+
+pub use summarizing::{Compaction, SummarizingSession};
+
 #[cfg(feature = "sqlite-session")]
 pub mod sqlite;
 
 #[cfg(feature = "sqlite-session")]
 pub use sqlite::SqliteSession;
 
+#[cfg(feature = "redis-session")]
+pub mod redis;
+
+#[cfg(feature = "redis-session")]
+pub use self::redis::RedisSession;
+
 /// In-memory session implementation (for testing and simple use cases)
 pub struct InMemorySession {
     items: std::sync::Arc<tokio::sync::Mutex<Vec<Value>>>,
+    settings: Option<SessionSettings>,
 }
 
 impl InMemorySession {
@@ -44,8 +74,15 @@ impl InMemorySession {
     pub fn new() -> Self {
         Self {
             items: std::sync::Arc::new(tokio::sync::Mutex::new(Vec::new())),
+            settings: None,
         }
     }
+
+    /// Set the settings applied when a run loads the history
+    pub fn with_settings(mut self, settings: SessionSettings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
 }
 
 impl Default for InMemorySession {
@@ -82,4 +119,13 @@ impl Session for InMemorySession {
         items.clear();
         Ok(())
     }
+
+    async fn replace_items(&self, new_items: Vec<Value>) -> Result<()> {
+        *self.items.lock().await = new_items;
+        Ok(())
+    }
+
+    fn settings(&self) -> Option<&SessionSettings> {
+        self.settings.as_ref()
+    }
 }
@@ -0,0 +1,154 @@
+//! Redis session implementation
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use redis::AsyncCommands;
+use redis::aio::ConnectionManager;
+use serde_json::Value;
+
+use crate::error::Result;
+
+use super::{Session, SessionSettings};
+
+/// Key prefix used when none is set
+const DEFAULT_KEY_PREFIX: &str = "agents:session";
+
+/// Redis-based session storage
+///
+/// Items are kept in a Redis list at `{key_prefix}:{session_id}`, oldest
+/// first. Every write is a single command or a `MULTI`/`EXEC` pipeline, so
+/// concurrent clients never observe a partial write.
+pub struct RedisSession {
+    session_id: String,
+    key_prefix: String,
+    ttl: Option<Duration>,
+    connection: ConnectionManager,
+    settings: Option<SessionSettings>,
+}
+
+impl RedisSession {
+    /// Connect to Redis at `url` (e.g. `redis://127.0.0.1/`)
+    pub async fn new(session_id: impl Into<String>, url: impl AsRef<str>) -> Result<Self> {
+        let client = redis::Client::open(url.as_ref())?;
+        let connection = ConnectionManager::new(client).await?;
+        Ok(Self::from_connection(session_id, connection))
+    }
+
+    /// Create a session sharing an existing connection
+    pub fn from_connection(session_id: impl Into<String>, connection: ConnectionManager) -> Self {
+        Self {
+            session_id: session_id.into(),
+            key_prefix: DEFAULT_KEY_PREFIX.to_string(),
+            ttl: None,
+            connection,
+            settings: None,
+        }
+    }
+
+    /// Set the prefix of the session key (defaults to `agents:session`)
+    pub fn with_key_prefix(mut self, key_prefix: impl Into<String>) -> Self {
+        self.key_prefix = key_prefix.into();
+        self
+    }
+
+    /// Expire the session after `ttl` without writes
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Set the settings applied when a run loads the history
+    pub fn with_settings(mut self, settings: SessionSettings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Session identifier
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Redis key holding the items
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.key_prefix, self.session_id)
+    }
+
+    /// Append `items`, after deleting the existing ones if `replace`, in one
+    /// `MULTI`/`EXEC` pipeline
+    async fn write(&self, items: Vec<Value>, replace: bool) -> Result<()> {
+        let data = items
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<serde_json::Result<Vec<_>>>()?;
+
+        let key = self.key();
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        if replace {
+            pipe.del(&key).ignore();
+        }
+        if !data.is_empty() {
+            pipe.rpush(&key, data).ignore();
+            if let Some(ttl) = self.ttl {
+                pipe.pexpire(&key, ttl.as_millis().min(i64::MAX as u128) as i64)
+                    .ignore();
+            }
+        }
+
+        let mut connection = self.connection.clone();
+        pipe.query_async::<_, ()>(&mut connection).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Session for RedisSession {
+    async fn get_items(&self, limit: Option<usize>) -> Result<Vec<Value>> {
+        // LRANGE with a negative start counts from the end, so the last
+        // `limit` items come back oldest first
+        let start = match limit {
+            Some(0) => return Ok(Vec::new()),
+            Some(n) => -(n.min(isize::MAX as usize) as isize),
+            None => 0,
+        };
+
+        let mut connection = self.connection.clone();
+        let data: Vec<String> = connection.lrange(self.key(), start, -1).await?;
+
+        data.iter()
+            .map(|item| Ok(serde_json::from_str(item)?))
+            .collect()
+    }
+
+    async fn add_items(&self, items: Vec<Value>) -> Result<()> {
+        if items.is_empty() {
+            return Ok(());
+        }
+        self.write(items, false).await
+    }
+
+    async fn pop_item(&self) -> Result<Option<Value>> {
+        let mut connection = self.connection.clone();
+        let data: Option<String> = connection.rpop(self.key(), None).await?;
+
+        match data {
+            Some(data) => Ok(Some(serde_json::from_str(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn clear_session(&self) -> Result<()> {
+        let mut connection = self.connection.clone();
+        connection.del::<_, ()>(self.key()).await?;
+        Ok(())
+    }
+
+    async fn replace_items(&self, items: Vec<Value>) -> Result<()> {
+        self.write(items, true).await
+    }
+
+    fn settings(&self) -> Option<&SessionSettings> {
+        self.settings.as_ref()
+    }
+}
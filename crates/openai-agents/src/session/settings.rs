@@ -0,0 +1,131 @@
+//! Session settings and history trimming
+
+use std::ops::Range;
+
+use serde_json::Value;
+
+/// How much of a session's history is sent to the model
+///
+/// Limits apply to the newest items. Items are trimmed in whole exchanges:
+/// an assistant message requesting tool calls is kept or dropped together
+/// with the tool results answering it, so the model never sees a call
+/// without its result or a result without its call.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionSettings {
+    /// Maximum number of items to keep in session
+    pub max_items: Option<usize>,
+    /// Maximum estimated tokens of the kept items (see [`estimate_tokens`])
+    pub max_tokens: Option<usize>,
+    /// Always keep the leading system messages and the first user message,
+    /// which usually state the task, even once they fall outside the limits
+    pub keep_initial_messages: bool,
+}
+
+impl SessionSettings {
+    /// Settings keeping the full history
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep at most the last `max_items` items
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    /// Keep the newest items within an estimated `max_tokens`
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Always keep the leading system messages and the first user message
+    pub fn keep_initial_messages(mut self, keep: bool) -> Self {
+        self.keep_initial_messages = keep;
+        self
+    }
+
+    /// Number of newest items a session needs to load to apply these
+    /// settings (`None` loads the full history)
+    pub fn fetch_limit(&self) -> Option<usize> {
+        if self.keep_initial_messages {
+            None
+        } else {
+            self.max_items
+        }
+    }
+
+    /// Trim `items`, oldest first, to these settings
+    pub fn trim(&self, mut items: Vec<Value>) -> Vec<Value> {
+        let pinned = if self.keep_initial_messages {
+            initial_len(&items)
+        } else {
+            0
+        };
+        let rest = items.split_off(pinned);
+
+        let mut count = items.len();
+        let mut tokens: usize = items.iter().map(estimate_tokens).sum();
+
+        // Walk whole exchanges back from the newest and keep them while
+        // they fit. A leading tool result lost its call to an earlier trim,
+        // so it is never kept.
+        let mut start = rest.len();
+        for exchange in exchanges(&rest).into_iter().rev() {
+            if role(&rest[exchange.start]) == Some("tool") {
+                break;
+            }
+
+            let exchange_tokens: usize = rest[exchange.clone()].iter().map(estimate_tokens).sum();
+            let fits = self
+                .max_items
+                .is_none_or(|max| count + exchange.len() <= max)
+                && self
+                    .max_tokens
+                    .is_none_or(|max| tokens + exchange_tokens <= max);
+            if !fits {
+                break;
+            }
+
+            count += exchange.len();
+            tokens += exchange_tokens;
+            start = exchange.start;
+        }
+
+        items.extend(rest.into_iter().skip(start));
+        items
+    }
+}
+
+/// Rough token count of a session item: a token per four bytes of JSON
+pub fn estimate_tokens(item: &Value) -> usize {
+    item.to_string().len().div_ceil(4)
+}
+
+fn role(item: &Value) -> Option<&str> {
+    item.get("role").and_then(Value::as_str)
+}
+
+/// Length of the leading system messages and the first user message
+fn initial_len(items: &[Value]) -> usize {
+    let system = items
+        .iter()
+        .take_while(|item| role(item) == Some("system"))
+        .count();
+    match items.get(system).and_then(role) {
+        Some("user") => system + 1,
+        _ => system,
+    }
+}
+
+/// Split items into exchanges: each item with the tool results following it
+fn exchanges(items: &[Value]) -> Vec<Range<usize>> {
+    let mut exchanges: Vec<Range<usize>> = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        match exchanges.last_mut() {
+            Some(last) if role(item) == Some("tool") => last.end = i + 1,
+            _ => exchanges.push(i..i + 1),
+        }
+    }
+    exchanges
+}
@@ -1,129 +1,274 @@
 //! SQLite session implementation
 
+use std::str::FromStr;
+
 use async_trait::async_trait;
 use serde_json::Value;
-use sqlx::{Row, sqlite::SqlitePool};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool};
+use sqlx::{Executor, SqliteConnection};
 
 use crate::error::{AgentError, Result};
 
-use super::Session;
+use super::{Session, SessionSettings};
+
+/// Table used when none is set
+const DEFAULT_TABLE: &str = "sessions";
+
+/// Table recording the schema version of each session table
+const VERSION_TABLE: &str = "session_schema_versions";
+
+/// Schema migrations, applied in order; `{table}` is the session table
+///
+/// The first migration matches the schema of databases created before
+/// versioning, so those are adopted as version 1. Append new migrations,
+/// never edit old ones.
+const MIGRATIONS: &[&str] = &[r#"
+    CREATE TABLE IF NOT EXISTS {table} (
+        session_id TEXT NOT NULL,
+        item_index INTEGER NOT NULL,
+        item_data TEXT NOT NULL,
+        created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (session_id, item_index)
+    )
+    "#];
 
 /// SQLite-based session storage
+///
+/// The database runs in WAL mode so readers don't block the writer, and
+/// every write is a single transaction, so several sessions (or processes)
+/// can share one database file.
 pub struct SqliteSession {
     session_id: String,
+    table: String,
     pool: SqlitePool,
+    settings: Option<SessionSettings>,
 }
 
 impl SqliteSession {
     /// Create a new SQLite session
+    ///
+    /// `db_path` is a file path or a `sqlite:` URL; the file is created if
+    /// missing.
     pub async fn new(session_id: impl Into<String>, db_path: impl AsRef<str>) -> Result<Self> {
-        let pool = SqlitePool::connect(db_path.as_ref())
-            .await
-            .map_err(|e| AgentError::SessionError(e.to_string()))?;
-
-        // Create table if it doesn't exist
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS sessions (
-                session_id TEXT NOT NULL,
-                item_index INTEGER NOT NULL,
-                item_data TEXT NOT NULL,
-                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                PRIMARY KEY (session_id, item_index)
-            )
-            "#,
-        )
-        .execute(&pool)
-        .await?;
+        Self::with_table(session_id, db_path, DEFAULT_TABLE).await
+    }
+
+    /// Create a new SQLite session storing its items in `table`
+    pub async fn with_table(
+        session_id: impl Into<String>,
+        db_path: impl AsRef<str>,
+        table: impl Into<String>,
+    ) -> Result<Self> {
+        let table = table.into();
+        if table.is_empty()
+            || table.starts_with(|c: char| c.is_ascii_digit())
+            || !table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(AgentError::ConfigError(format!(
+                "Invalid session table name: {:?}",
+                table
+            )));
+        }
+
+        let options = SqliteConnectOptions::from_str(db_path.as_ref())?
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal);
+        let pool = SqlitePool::connect_with(options).await?;
 
-        Ok(Self {
+        let session = Self {
             session_id: session_id.into(),
+            table,
             pool,
-        })
+            settings: None,
+        };
+        session.migrate().await?;
+        Ok(session)
     }
-}
 
-#[async_trait]
-impl Session for SqliteSession {
-    async fn get_items(&self, limit: Option<usize>) -> Result<Vec<Value>> {
-        let query = if let Some(limit) = limit {
-            format!(
-                "SELECT item_data FROM sessions WHERE session_id = ? ORDER BY item_index DESC LIMIT {}",
-                limit
-            )
-        } else {
-            "SELECT item_data FROM sessions WHERE session_id = ? ORDER BY item_index ASC"
-                .to_string()
-        };
+    /// Set the settings applied when a run loads the history
+    pub fn with_settings(mut self, settings: SessionSettings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
 
-        let rows = sqlx::query(&query)
-            .bind(&self.session_id)
-            .fetch_all(&self.pool)
-            .await?;
+    /// Session identifier
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
 
-        let mut items = Vec::new();
-        for row in rows {
-            let data: String = row.try_get("item_data")?;
-            let value: Value = serde_json::from_str(&data)?;
-            items.push(value);
-        }
+    /// Table holding the items
+    pub fn table(&self) -> &str {
+        &self.table
+    }
 
-        Ok(items)
+    /// Schema version of the session table
+    pub async fn schema_version(&self) -> Result<i64> {
+        let version = sqlx::query_scalar(&format!(
+            "SELECT version FROM {} WHERE table_name = ?",
+            VERSION_TABLE
+        ))
+        .bind(&self.table)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(version.unwrap_or(0))
     }
 
-    async fn add_items(&self, items: Vec<Value>) -> Result<()> {
+    /// Append `items` within a transaction
+    async fn insert_items(&self, tx: &mut SqliteConnection, items: Vec<Value>) -> Result<()> {
+        // Each insert computes its own index, so the transaction writes
+        // from its first statement and concurrent writers queue on the
+        // database lock instead of racing for the same index
+        let insert = format!(
+            "INSERT INTO {table} (session_id, item_index, item_data)
+             SELECT ?1, COALESCE(MAX(item_index), -1) + 1, ?2 FROM {table} WHERE session_id = ?1",
+            table = self.table
+        );
         for item in items {
-            let data = serde_json::to_string(&item)?;
+            sqlx::query(&insert)
+                .bind(&self.session_id)
+                .bind(serde_json::to_string(&item)?)
+                .execute(&mut *tx)
+                .await?;
+        }
 
-            // Get the next index
-            let next_index: i64 = sqlx::query_scalar(
-                "SELECT COALESCE(MAX(item_index), -1) + 1 FROM sessions WHERE session_id = ?",
-            )
-            .bind(&self.session_id)
-            .fetch_one(&self.pool)
-            .await?;
+        Ok(())
+    }
 
-            sqlx::query(
-                "INSERT INTO sessions (session_id, item_index, item_data) VALUES (?, ?, ?)",
-            )
-            .bind(&self.session_id)
-            .bind(next_index)
-            .bind(data)
-            .execute(&self.pool)
+    /// Bring the session table up to the latest schema
+    async fn migrate(&self) -> Result<()> {
+        let mut conn = self.pool.acquire().await?;
+
+        // Take the write lock up front so concurrent openers apply each
+        // migration once
+        conn.execute("BEGIN IMMEDIATE").await?;
+        match self.apply_migrations(&mut conn).await {
+            Ok(()) => {
+                conn.execute("COMMIT").await?;
+                Ok(())
+            }
+            Err(e) => {
+                let _ = conn.execute("ROLLBACK").await;
+                Err(e)
+            }
+        }
+    }
+
+    async fn apply_migrations(&self, conn: &mut SqliteConnection) -> Result<()> {
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {} (table_name TEXT PRIMARY KEY, version INTEGER NOT NULL)",
+            VERSION_TABLE
+        ))
+        .execute(&mut *conn)
+        .await?;
+
+        let current: i64 = sqlx::query_scalar(&format!(
+            "SELECT COALESCE(MAX(version), 0) FROM {} WHERE table_name = ?",
+            VERSION_TABLE
+        ))
+        .bind(&self.table)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        for (version, migration) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+            conn.execute(migration.replace("{table}", &self.table).as_str())
+                .await?;
+            sqlx::query(&format!(
+                "INSERT OR REPLACE INTO {} (table_name, version) VALUES (?, ?)",
+                VERSION_TABLE
+            ))
+            .bind(&self.table)
+            .bind(version as i64 + 1)
+            .execute(&mut *conn)
             .await?;
         }
 
         Ok(())
     }
+}
+
+#[async_trait]
+impl Session for SqliteSession {
+    async fn get_items(&self, limit: Option<usize>) -> Result<Vec<Value>> {
+        // The newest `limit` items, returned oldest first
+        let rows: Vec<String> = match limit {
+            Some(limit) => {
+                sqlx::query_scalar(&format!(
+                    "SELECT item_data FROM (
+                        SELECT item_index, item_data FROM {} WHERE session_id = ?
+                        ORDER BY item_index DESC LIMIT ?
+                    ) ORDER BY item_index ASC",
+                    self.table
+                ))
+                .bind(&self.session_id)
+                .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+                .fetch_all(&self.pool)
+                .await?
+            }
+            None => {
+                sqlx::query_scalar(&format!(
+                    "SELECT item_data FROM {} WHERE session_id = ? ORDER BY item_index ASC",
+                    self.table
+                ))
+                .bind(&self.session_id)
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+
+        rows.iter()
+            .map(|data| Ok(serde_json::from_str(data)?))
+            .collect()
+    }
+
+    async fn add_items(&self, items: Vec<Value>) -> Result<()> {
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        self.insert_items(&mut tx, items).await?;
+        tx.commit().await?;
+        Ok(())
+    }
 
     async fn pop_item(&self) -> Result<Option<Value>> {
-        let row: Option<(i64, String)> = sqlx::query_as(
-            "SELECT item_index, item_data FROM sessions WHERE session_id = ? ORDER BY item_index DESC LIMIT 1"
-        )
+        let data: Option<String> = sqlx::query_scalar(&format!(
+            "DELETE FROM {table} WHERE session_id = ?1 AND item_index = (
+                SELECT MAX(item_index) FROM {table} WHERE session_id = ?1
+            ) RETURNING item_data",
+            table = self.table
+        ))
         .bind(&self.session_id)
         .fetch_optional(&self.pool)
         .await?;
 
-        if let Some((index, data)) = row {
-            sqlx::query("DELETE FROM sessions WHERE session_id = ? AND item_index = ?")
-                .bind(&self.session_id)
-                .bind(index)
-                .execute(&self.pool)
-                .await?;
-
-            let value: Value = serde_json::from_str(&data)?;
-            Ok(Some(value))
-        } else {
-            Ok(None)
+        match data {
+            Some(data) => Ok(Some(serde_json::from_str(&data)?)),
+            None => Ok(None),
         }
     }
 
     async fn clear_session(&self) -> Result<()> {
-        sqlx::query("DELETE FROM sessions WHERE session_id = ?")
+        sqlx::query(&format!("DELETE FROM {} WHERE session_id = ?", self.table))
             .bind(&self.session_id)
             .execute(&self.pool)
             .await?;
 
         Ok(())
     }
+
+    async fn replace_items(&self, items: Vec<Value>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(&format!("DELETE FROM {} WHERE session_id = ?", self.table))
+            .bind(&self.session_id)
+            .execute(&mut *tx)
+            .await?;
+        self.insert_items(&mut tx, items).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    fn settings(&self) -> Option<&SessionSettings> {
+        self.settings.as_ref()
+    }
 }
@@ -0,0 +1,281 @@
+//! Session that compacts old history into a model-written summary
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::error::{AgentError, Result};
+use crate::models::{CompletionRequest, Message, ModelProvider};
+
+use super::{Session, SessionSettings, estimate_tokens};
+
+/// Instructions given to the summarizing model unless overridden
+const DEFAULT_INSTRUCTIONS: &str = "Summarize the conversation you are given for the assistant \
+     that will continue it. Keep the user's goals, facts and decisions, open questions, and the \
+     results of tool calls that still matter. Be concise and write in plain prose.";
+
+/// Record of a compaction, stored on the summary item that replaced the
+/// compacted items
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Compaction {
+    /// Number of original items the summary stands for, across compactions
+    pub summarized_items: usize,
+    /// When the summary was written
+    pub created_at: DateTime<Utc>,
+}
+
+impl Compaction {
+    /// The compaction recorded on `item`, if it is a summary
+    pub fn from_item(item: &Value) -> Option<Self> {
+        serde_json::from_value(item.get("compaction")?.clone()).ok()
+    }
+}
+
+/// Session decorator that summarizes old history with a model
+///
+/// Once the inner session holds more than `max_items` items (or, if set,
+/// the items older than the newest `keep_recent` hold more than
+/// `max_tokens` estimated tokens not summarized yet), everything but the
+/// newest `keep_recent` items is replaced by a single user message
+/// summarizing it. The summary records a [`Compaction`] and counts as one
+/// item, so the history only compacts again after it has grown past the
+/// limit again; a later summary folds in the earlier one.
+///
+/// Compaction rewrites the inner session with [`Session::replace_items`],
+/// so it should not be written to from elsewhere meanwhile. If summarizing
+/// fails, the new items are kept and compaction is retried on the next
+/// write; if rewriting fails, `add_items` returns the error.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use openai_agents::OpenAIResponsesModel;
+/// use openai_agents::{InMemorySession, SummarizingSession};
+///
+/// let session = SummarizingSession::new(
+///     Arc::new(InMemorySession::new()),
+///     Arc::new(OpenAIResponsesModel::new()),
+/// )
+/// .with_max_items(40)
+/// .with_keep_recent(10);
+/// ```
+pub struct SummarizingSession {
+    inner: Arc<dyn Session>,
+    model: Arc<dyn ModelProvider>,
+    model_name: String,
+    instructions: String,
+    max_items: usize,
+    max_tokens: Option<usize>,
+    keep_recent: usize,
+    lock: Mutex<()>,
+}
+
+impl SummarizingSession {
+    /// Wrap `inner`, summarizing with `model`
+    pub fn new(inner: Arc<dyn Session>, model: Arc<dyn ModelProvider>) -> Self {
+        Self {
+            inner,
+            model,
+            model_name: "gpt-4o-mini".to_string(),
+            instructions: DEFAULT_INSTRUCTIONS.to_string(),
+            max_items: 50,
+            max_tokens: None,
+            keep_recent: 10,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Set the model name used for summaries
+    pub fn with_model(mut self, model_name: impl Into<String>) -> Self {
+        self.model_name = model_name.into();
+        self
+    }
+
+    /// Set the instructions of the summarizing model
+    pub fn with_instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.instructions = instructions.into();
+        self
+    }
+
+    /// Compact once the history holds more than `max_items` items
+    pub fn with_max_items(mut self, max_items: usize) -> Self {
+        self.max_items = max_items;
+        self
+    }
+
+    /// Also compact once the items older than the recent ones exceed an
+    /// estimated `max_tokens`
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Keep the newest `keep_recent` items verbatim when compacting
+    pub fn with_keep_recent(mut self, keep_recent: usize) -> Self {
+        self.keep_recent = keep_recent;
+        self
+    }
+
+    /// The latest compaction, if the history starts with a summary
+    pub async fn compaction(&self) -> Result<Option<Compaction>> {
+        let items = self.inner.get_items(None).await?;
+        Ok(items.first().and_then(Compaction::from_item))
+    }
+
+    /// Compact the history now if it exceeds the limits
+    ///
+    /// Returns whether the history was compacted.
+    pub async fn compact(&self) -> Result<bool> {
+        let _guard = self.lock.lock().await;
+        match self.compacted().await? {
+            Some(items) => {
+                self.inner.replace_items(items).await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// The summary and recent items to replace the history with, if it
+    /// exceeds the limits
+    async fn compacted(&self) -> Result<Option<Vec<Value>>> {
+        let mut items = self.inner.get_items(None).await?;
+
+        // Recent items are cut like session history, never separating a
+        // tool call from its results
+        let recent = SessionSettings::new()
+            .with_max_items(self.keep_recent)
+            .trim(items.clone());
+        let older: Vec<Value> = items.drain(..items.len() - recent.len()).collect();
+
+        // Only items not summarized yet count against the token limit: the
+        // recent items and the previous summary stay whatever their size, so
+        // counting them would summarize again on every write
+        let unsummarized = older
+            .iter()
+            .filter(|item| Compaction::from_item(item).is_none());
+        let over_tokens = self
+            .max_tokens
+            .is_some_and(|max| unsummarized.clone().map(estimate_tokens).sum::<usize>() > max);
+        let over_items = older.len() + recent.len() > self.max_items;
+        if unsummarized.count() == 0 || !(over_items || over_tokens) {
+            return Ok(None);
+        }
+
+        let summarized_items = older
+            .iter()
+            .map(|item| Compaction::from_item(item).map_or(1, |c| c.summarized_items))
+            .sum();
+        let summary = self.summarize(&older).await?;
+        let compaction = Compaction {
+            summarized_items,
+            created_at: Utc::now(),
+        };
+
+        // A user message rather than a system one: the runner owns the
+        // leading system message and rewrites it on handoffs
+        let mut summary = serde_json::to_value(Message::user(format!(
+            "Summary of the earlier conversation:\n{}",
+            summary
+        )))?;
+        summary["compaction"] = serde_json::to_value(compaction)?;
+
+        Ok(Some(std::iter::once(summary).chain(recent).collect()))
+    }
+
+    /// Ask the model for a summary of `items`
+    async fn summarize(&self, items: &[Value]) -> Result<String> {
+        let transcript = items.iter().map(render).collect::<Vec<_>>().join("\n\n");
+        let request = CompletionRequest {
+            messages: vec![
+                Message::system(self.instructions.clone()),
+                Message::user(transcript),
+            ],
+            model: self.model_name.clone(),
+            tools: None,
+            parallel_tool_calls: None,
+            max_tokens: None,
+            temperature: None,
+            response_format: None,
+            tool_choice: None,
+        };
+
+        self.model
+            .complete(request)
+            .await?
+            .content
+            .filter(|summary| !summary.trim().is_empty())
+            .ok_or_else(|| AgentError::SessionError("Summarizing model returned no summary".into()))
+    }
+}
+
+/// Render a session item as a line of a plain-text transcript
+fn render(item: &Value) -> String {
+    match serde_json::from_value::<Message>(item.clone()) {
+        Ok(Message::System { content }) => format!("system: {}", content),
+        Ok(Message::User { content }) => format!("user: {}", content),
+        Ok(Message::Assistant {
+            content,
+            tool_calls,
+        }) => {
+            let calls = tool_calls
+                .iter()
+                .map(|call| format!("[called {}({})]", call.name, call.arguments));
+            let parts: Vec<_> = content.into_iter().chain(calls).collect();
+            format!("assistant: {}", parts.join(" "))
+        }
+        Ok(Message::Tool { content, .. }) => format!("tool result: {}", content),
+        Err(_) => item.to_string(),
+    }
+}
+
+#[async_trait]
+impl Session for SummarizingSession {
+    async fn get_items(&self, limit: Option<usize>) -> Result<Vec<Value>> {
+        self.inner.get_items(limit).await
+    }
+
+    async fn add_items(&self, items: Vec<Value>) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        self.inner.add_items(items).await?;
+
+        // A failed summary leaves the history as it is, but a failed write
+        // of the compacted history may not have
+        match self.compacted().await {
+            Ok(Some(items)) => self.inner.replace_items(items).await,
+            Ok(None) => Ok(()),
+            Err(e) => {
+                tracing::warn!("Failed to summarize session history: {}", e);
+                Ok(())
+            }
+        }
+    }
+
+    async fn pop_item(&self) -> Result<Option<Value>> {
+        let _guard = self.lock.lock().await;
+        self.inner.pop_item().await
+    }
+
+    async fn clear_session(&self) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        self.inner.clear_session().await
+    }
+
+    async fn replace_items(&self, items: Vec<Value>) -> Result<()> {
+        let _guard = self.lock.lock().await;
+        self.inner.replace_items(items).await
+    }
+
+    fn settings(&self) -> Option<&SessionSettings> {
+        self.inner.settings()
+    }
+
+    async fn get_history(&self, settings: &SessionSettings) -> Result<Vec<Value>> {
+        self.inner.get_history(settings).await
+    }
+}
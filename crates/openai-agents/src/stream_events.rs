@@ -1,5 +1,6 @@
 use crate::agent::Agent;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Types of run items that can be streamed
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,7 +72,16 @@ pub enum RunItemEventName {
 #[derive(Clone)]
 pub struct AgentUpdatedEvent {
     /// The new agent
-    pub new_agent: Agent,
+    pub new_agent: Arc<Agent>,
+}
+
+/// Event that notifies a guardrail blocked the run
+#[derive(Debug, Clone)]
+pub struct GuardrailTrippedEvent {
+    /// Name of the guardrail
+    pub guardrail: String,
+    /// Why the guardrail tripped
+    pub reason: String,
 }
 
 /// A streaming event from an agent
@@ -83,6 +93,8 @@ pub enum StreamEvent {
     RunItem(RunItemStreamEvent),
     /// Agent updated event
     AgentUpdated(AgentUpdatedEvent),
+    /// A guardrail tripped; the run ends with the matching error
+    GuardrailTripped(GuardrailTrippedEvent),
 }
 
 impl StreamEvent {
@@ -92,6 +104,7 @@ impl StreamEvent {
             StreamEvent::RawResponse(_) => "raw_response_event",
             StreamEvent::RunItem(_) => "run_item_stream_event",
             StreamEvent::AgentUpdated(_) => "agent_updated_stream_event",
+            StreamEvent::GuardrailTripped(_) => "guardrail_tripped_event",
         }
     }
 }
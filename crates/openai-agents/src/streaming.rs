@@ -4,11 +4,15 @@ use tokio::sync::mpsc;
 
 use crate::{error::Result, result::RunResult, stream_events::StreamEvent};
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
+use crate::usage::{RunUsage, Usage};
+
 /// Shared state for StreamedRunResult
 pub(crate) struct SharedState {
     pub(crate) final_result: Option<RunResult>,
+    pub(crate) error: Option<crate::error::AgentError>,
 }
 
 /// Result from a streamed agent run
@@ -18,18 +22,25 @@ pub struct StreamedRunResult {
     receiver: Arc<tokio::sync::Mutex<Option<mpsc::UnboundedReceiver<StreamEvent>>>>,
     /// Shared state
     shared_state: Arc<std::sync::Mutex<SharedState>>,
+    /// Usage so far, updated after every model call
+    usage: Arc<std::sync::Mutex<RunUsage>>,
 }
 
 impl StreamedRunResult {
     /// Create a new streamed run result
     pub(crate) fn new(
         receiver: mpsc::UnboundedReceiver<StreamEvent>,
+        usage: Arc<std::sync::Mutex<RunUsage>>,
     ) -> (Self, Arc<std::sync::Mutex<SharedState>>) {
-        let shared_state = Arc::new(std::sync::Mutex::new(SharedState { final_result: None }));
+        let shared_state = Arc::new(std::sync::Mutex::new(SharedState {
+            final_result: None,
+            error: None,
+        }));
         (
             Self {
                 receiver: Arc::new(tokio::sync::Mutex::new(Some(receiver))),
                 shared_state: shared_state.clone(),
+                usage,
             },
             shared_state,
         )
@@ -49,6 +60,9 @@ impl StreamedRunResult {
     }
 
     /// Wait for the final result
+    ///
+    /// Returns the error that aborted the run, if any. The error is handed out
+    /// only once; later calls report that no final result is available.
     pub async fn final_result(&self) -> Result<RunResult> {
         // Drain all events if not already done
         {
@@ -58,12 +72,33 @@ impl StreamedRunResult {
             }
         }
 
-        let state = self.shared_state.lock().unwrap();
+        let mut state = self.shared_state.lock().unwrap();
+        if let Some(error) = state.error.take() {
+            return Err(error);
+        }
         state.final_result.clone().ok_or_else(|| {
             crate::error::AgentError::ConfigError("No final result available".to_string())
         })
     }
 
+    /// Tokens used by the model calls of the run so far
+    ///
+    /// Unlike the final result, this is also available while the run is in
+    /// progress and after it failed.
+    pub fn usage(&self) -> Usage {
+        self.usage.lock().unwrap().total
+    }
+
+    /// Tokens used by the model calls of each agent so far, keyed by agent name
+    pub fn usage_by_agent(&self) -> BTreeMap<String, Usage> {
+        self.usage.lock().unwrap().by_agent.clone()
+    }
+
+    /// Estimated cost in USD of the run so far, if any model called has a price
+    pub fn cost(&self) -> Option<f64> {
+        self.usage.lock().unwrap().cost()
+    }
+
     /// Get the final output directly (convenience)
     pub async fn final_output(&self) -> Result<String> {
         let result = self.final_result().await?;
@@ -1,9 +1,19 @@
 //! Tool trait and implementations
 
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
 use async_trait::async_trait;
+use futures::future::BoxFuture;
+use schemars::JsonSchema;
+use serde::{Serialize, de::DeserializeOwned};
 use serde_json::Value;
 
-use crate::error::Result;
+use crate::context::RunContext;
+use crate::error::{AgentError, Result};
+use crate::guardrail::{ToolInputGuardrail, ToolOutputGuardrail};
+use crate::schema::strict_schema_for;
 
 /// Trait for tools that agents can use
 #[async_trait]
@@ -18,26 +28,203 @@ pub trait Tool: Send + Sync {
     fn parameters_schema(&self) -> Value;
 
     /// Execute the tool with the given arguments
-    async fn execute(&self, args: Value) -> Result<Value>;
+    ///
+    /// `ctx` carries the application context of the run, the calling agent
+    /// and the id of the tool call being executed.
+    async fn execute(&self, ctx: &RunContext, args: Value) -> Result<Value>;
+
+    /// Maximum time a single execution may take
+    ///
+    /// `None` falls back to `RunConfig::tool_timeout`. When the timeout
+    /// elapses the execution future is dropped and the call fails with
+    /// `AgentError::ToolTimeout`.
+    fn timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Guardrails checking the arguments of every call to this tool
+    ///
+    /// They run after the guardrails of the run and of the agent.
+    fn input_guardrails(&self) -> &[Arc<dyn ToolInputGuardrail>] {
+        &[]
+    }
+
+    /// Guardrails checking the output of every call to this tool
+    ///
+    /// They run after the guardrails of the run and of the agent.
+    fn output_guardrails(&self) -> &[Arc<dyn ToolOutputGuardrail>] {
+        &[]
+    }
 }
 
+type ToolFn = Arc<dyn Fn(RunContext, Value) -> BoxFuture<'static, Result<Value>> + Send + Sync>;
+
 /// A function-based tool implementation
+///
+/// Useful for tools that are only known at runtime (loaded from config,
+/// generated per tenant, ...). For tools known at compile time prefer the
+/// `#[function_tool]` macro.
+///
+/// # Example
+///
+/// ```rust
+/// use openai_agents::{Agent, FunctionTool};
+/// use schemars::JsonSchema;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, JsonSchema)]
+/// struct AddArgs {
+///     a: i64,
+///     b: i64,
+/// }
+///
+/// let add = FunctionTool::typed_sync("add", "Add two numbers", |args: AddArgs| {
+///     Ok(args.a + args.b)
+/// });
+///
+/// let agent = Agent::builder("Calculator").tool(add).build();
+/// ```
+#[derive(Clone)]
 pub struct FunctionTool {
     name: String,
     description: String,
     schema: Value,
-    // TODO: Add function pointer/closure for execution
+    function: ToolFn,
+    timeout: Option<Duration>,
+    input_guardrails: Vec<Arc<dyn ToolInputGuardrail>>,
+    output_guardrails: Vec<Arc<dyn ToolOutputGuardrail>>,
 }
 
 impl FunctionTool {
-    /// Create a new function tool
-    pub fn new(name: impl Into<String>, description: impl Into<String>, schema: Value) -> Self {
+    /// Create a new function tool from an async closure over raw JSON arguments
+    pub fn new<F, Fut>(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        schema: Value,
+        function: F,
+    ) -> Self
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Value>> + Send + 'static,
+    {
+        Self::with_context(name, description, schema, move |_ctx, args| function(args))
+    }
+
+    /// Create a new function tool from an async closure that also receives the run context
+    pub fn with_context<F, Fut>(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        schema: Value,
+        function: F,
+    ) -> Self
+    where
+        F: Fn(RunContext, Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Value>> + Send + 'static,
+    {
         Self {
             name: name.into(),
             description: description.into(),
             schema,
+            function: Arc::new(move |ctx, args| Box::pin(function(ctx, args))),
+            timeout: None,
+            input_guardrails: Vec::new(),
+            output_guardrails: Vec::new(),
         }
     }
+
+    /// Set the maximum time a single execution may take
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Add a guardrail checking the arguments of every call
+    pub fn with_input_guardrail(mut self, guardrail: impl ToolInputGuardrail + 'static) -> Self {
+        self.input_guardrails.push(Arc::new(guardrail));
+        self
+    }
+
+    /// Add a guardrail checking the output of every call
+    pub fn with_output_guardrail(mut self, guardrail: impl ToolOutputGuardrail + 'static) -> Self {
+        self.output_guardrails.push(Arc::new(guardrail));
+        self
+    }
+
+    /// Create a new function tool from a synchronous closure over raw JSON arguments
+    ///
+    /// The closure runs on the runner's task, so it should not block.
+    pub fn new_sync<F>(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        schema: Value,
+        function: F,
+    ) -> Self
+    where
+        F: Fn(Value) -> Result<Value> + Send + Sync + 'static,
+    {
+        Self::new(name, description, schema, move |args| {
+            std::future::ready(function(args))
+        })
+    }
+
+    /// Create a new function tool from an async closure over typed arguments
+    ///
+    /// The parameters schema is derived from `T`, and the JSON arguments are
+    /// deserialized into `T` before the closure is called.
+    pub fn typed<T, R, F, Fut>(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        function: F,
+    ) -> Self
+    where
+        T: DeserializeOwned + JsonSchema + Send + 'static,
+        R: Serialize,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R>> + Send + 'static,
+    {
+        let name = name.into();
+        let tool_name = name.clone();
+        let function = Arc::new(function);
+        Self::new(name, description, strict_schema_for::<T>(), move |args| {
+            let tool_name = tool_name.clone();
+            let function = function.clone();
+            async move {
+                let args = parse_args::<T>(&tool_name, args)?;
+                let output = function(args).await?;
+                serialize_output(&tool_name, output)
+            }
+        })
+    }
+
+    /// Create a new function tool from a synchronous closure over typed arguments
+    ///
+    /// The closure runs on the runner's task, so it should not block.
+    pub fn typed_sync<T, R, F>(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        function: F,
+    ) -> Self
+    where
+        T: DeserializeOwned + JsonSchema,
+        R: Serialize,
+        F: Fn(T) -> Result<R> + Send + Sync + 'static,
+    {
+        let name = name.into();
+        let tool_name = name.clone();
+        Self::new_sync(name, description, strict_schema_for::<T>(), move |args| {
+            let args = parse_args::<T>(&tool_name, args)?;
+            serialize_output(&tool_name, function(args)?)
+        })
+    }
+}
+
+fn parse_args<T: DeserializeOwned>(tool_name: &str, args: Value) -> Result<T> {
+    serde_json::from_value(args)
+        .map_err(|e| AgentError::tool_failed(tool_name, format!("Invalid arguments: {}", e)))
+}
+
+fn serialize_output<R: Serialize>(tool_name: &str, output: R) -> Result<Value> {
+    serde_json::to_value(output).map_err(|e| AgentError::tool_failed(tool_name, e.to_string()))
 }
 
 #[async_trait]
@@ -54,8 +241,59 @@ impl Tool for FunctionTool {
         self.schema.clone()
     }
 
-    async fn execute(&self, _args: Value) -> Result<Value> {
-        // TODO: Implement execution
-        Ok(Value::Null)
+    async fn execute(&self, ctx: &RunContext, args: Value) -> Result<Value> {
+        (self.function)(ctx.clone(), args).await
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn input_guardrails(&self) -> &[Arc<dyn ToolInputGuardrail>] {
+        &self.input_guardrails
+    }
+
+    fn output_guardrails(&self) -> &[Arc<dyn ToolOutputGuardrail>] {
+        &self.output_guardrails
+    }
+}
+
+type ToolErrorFormatter = Arc<dyn Fn(&str, &AgentError) -> String + Send + Sync>;
+
+/// How the runner handles a tool call that fails
+///
+/// Failures include errors returned by the tool and calls to tools the agent
+/// doesn't have. Unless the run is aborted, the resulting text is sent back to
+/// the model as the tool's output so it can retry or work around the failure.
+#[derive(Clone, Default)]
+pub enum ToolErrorPolicy {
+    /// Abort the run with the tool's error
+    #[default]
+    Abort,
+    /// Send the error message back to the model as the tool result
+    ReturnToModel,
+    /// Send the text produced by a formatter back to the model as the tool result
+    Format(ToolErrorFormatter),
+}
+
+impl ToolErrorPolicy {
+    /// Create a policy that formats the tool name and error into the tool result
+    pub fn format<F>(formatter: F) -> Self
+    where
+        F: Fn(&str, &AgentError) -> String + Send + Sync + 'static,
+    {
+        Self::Format(Arc::new(formatter))
+    }
+
+    /// Apply the policy to a failed tool call, returning the output for the model
+    pub(crate) fn handle(&self, tool_name: &str, error: AgentError) -> Result<Value> {
+        match self {
+            Self::Abort => Err(error),
+            Self::ReturnToModel => Ok(Value::String(format!(
+                "An error occurred while running the tool. Please try again. Error: {}",
+                error
+            ))),
+            Self::Format(formatter) => Ok(Value::String(formatter(tool_name, &error))),
+        }
     }
 }
@@ -0,0 +1,93 @@
+//! Token usage accounting
+
+use std::collections::BTreeMap;
+use std::ops::{Add, AddAssign};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::pricing::PriceTable;
+
+/// Token usage of one or more model calls
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Usage {
+    /// Number of model calls
+    pub requests: u64,
+    /// Tokens in the prompts
+    pub prompt_tokens: u64,
+    /// Tokens in the completions
+    pub completion_tokens: u64,
+    /// Prompt tokens served from the provider's cache
+    pub cached_tokens: u64,
+    /// Completion tokens spent on reasoning
+    pub reasoning_tokens: u64,
+    /// Prompt and completion tokens together
+    pub total_tokens: u64,
+}
+
+impl Add for Usage {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl AddAssign for Usage {
+    fn add_assign(&mut self, other: Self) {
+        self.requests += other.requests;
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.cached_tokens += other.cached_tokens;
+        self.reasoning_tokens += other.reasoning_tokens;
+        self.total_tokens += other.total_tokens;
+    }
+}
+
+/// Usage and estimated cost of one model call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelCall {
+    /// Agent that made the call
+    pub agent_name: String,
+    /// Model that was called
+    pub model: String,
+    /// Tokens used by the call
+    pub usage: Usage,
+    /// Estimated cost in USD, if the model has a price
+    pub cost: Option<f64>,
+}
+
+/// Usage of a run, in total, per agent and per call
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RunUsage {
+    pub(crate) total: Usage,
+    pub(crate) by_agent: BTreeMap<String, Usage>,
+    pub(crate) calls: Vec<ModelCall>,
+}
+
+impl RunUsage {
+    /// Account a model call
+    pub(crate) fn record(&mut self, call: ModelCall) {
+        self.total += call.usage;
+        *self.by_agent.entry(call.agent_name.clone()).or_default() += call.usage;
+        self.calls.push(call);
+    }
+
+    /// Estimated cost in USD of the calls with a price, if any
+    pub(crate) fn cost(&self) -> Option<f64> {
+        self.calls
+            .iter()
+            .filter_map(|call| call.cost)
+            .reduce(|total, cost| total + cost)
+    }
+}
+
+/// Usage of a run with the prices and budget it is accounted against,
+/// shared with nested runs (such as guardrail judges) made on its behalf
+#[derive(Clone)]
+pub(crate) struct UsageMeter {
+    pub(crate) usage: Arc<Mutex<RunUsage>>,
+    pub(crate) price_table: Option<Arc<PriceTable>>,
+    pub(crate) max_cost: Option<f64>,
+}
@@ -0,0 +1,136 @@
+//! Shared helpers for integration tests
+
+#![allow(dead_code)]
+
+pub mod session;
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use futures::{StreamExt, stream};
+use openai_agents::Usage;
+use openai_agents::error::{AgentError, Result};
+use openai_agents::models::{
+    CompletionRequest, CompletionResponse, CompletionStream, ModelProvider, StreamChunk, ToolCall,
+    ToolCallDelta,
+};
+use serde_json::Value;
+
+/// A model that replays a fixed script of responses and records every request
+pub struct ScriptedModel {
+    responses: Mutex<VecDeque<CompletionResponse>>,
+    requests: Mutex<Vec<CompletionRequest>>,
+}
+
+impl ScriptedModel {
+    pub fn new(responses: Vec<CompletionResponse>) -> Self {
+        Self {
+            responses: Mutex::new(responses.into()),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Requests received so far
+    pub fn requests(&self) -> Vec<CompletionRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn next_response(&self, request: CompletionRequest) -> Result<CompletionResponse> {
+        self.requests.lock().unwrap().push(request);
+        self.responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or_else(|| AgentError::ModelError("script exhausted".to_string()))
+    }
+}
+
+#[async_trait]
+impl ModelProvider for ScriptedModel {
+    async fn complete(&self, request: CompletionRequest) -> Result<CompletionResponse> {
+        self.next_response(request)
+    }
+
+    async fn stream(&self, request: CompletionRequest) -> Result<CompletionStream> {
+        let response = self.next_response(request)?;
+
+        let mut chunks = Vec::new();
+        if let Some(content) = response.content {
+            chunks.push(StreamChunk {
+                delta: Some(content),
+                tool_call_deltas: vec![],
+                finish_reason: None,
+                usage: None,
+            });
+        }
+        for (index, call) in response.tool_calls.into_iter().enumerate() {
+            chunks.push(StreamChunk {
+                delta: None,
+                tool_call_deltas: vec![ToolCallDelta {
+                    index,
+                    id: Some(call.id),
+                    name: Some(call.name),
+                    arguments: Some(call.arguments.to_string()),
+                }],
+                finish_reason: None,
+                usage: None,
+            });
+        }
+        chunks.push(StreamChunk {
+            delta: None,
+            tool_call_deltas: vec![],
+            finish_reason: response.finish_reason,
+            usage: response.usage,
+        });
+
+        Ok(CompletionStream::new(
+            stream::iter(chunks.into_iter().map(Ok)).boxed(),
+        ))
+    }
+}
+
+/// A final text response
+pub fn text(content: &str) -> CompletionResponse {
+    CompletionResponse {
+        content: Some(content.to_string()),
+        tool_calls: vec![],
+        finish_reason: Some("stop".to_string()),
+        usage: None,
+    }
+}
+
+/// A response calling the given tools, as `(id, name, arguments)`
+pub fn tool_calls(calls: &[(&str, &str, Value)]) -> CompletionResponse {
+    CompletionResponse {
+        content: None,
+        tool_calls: calls
+            .iter()
+            .map(|(id, name, arguments)| ToolCall {
+                id: id.to_string(),
+                name: name.to_string(),
+                arguments: arguments.clone(),
+            })
+            .collect(),
+        finish_reason: Some("tool_calls".to_string()),
+        usage: None,
+    }
+}
+
+/// A response reporting the given prompt and completion tokens
+pub fn with_usage(
+    response: CompletionResponse,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+) -> CompletionResponse {
+    CompletionResponse {
+        usage: Some(Usage {
+            requests: 1,
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            ..Default::default()
+        }),
+        ..response
+    }
+}
@@ -0,0 +1,115 @@
+//! Conformance suite every `Session` implementation must pass
+
+use openai_agents::Session;
+use serde_json::json;
+
+/// Run every check against `session`, which must start empty
+pub async fn check_session(session: &dyn Session) {
+    check_items_in_order(session).await;
+    check_limit(session).await;
+    check_pop_item(session).await;
+    check_clear_session(session).await;
+    check_replace_items(session).await;
+    check_concurrent_writes(session).await;
+}
+
+/// Items come back oldest first, across batches
+async fn check_items_in_order(session: &dyn Session) {
+    assert!(session.get_items(None).await.unwrap().is_empty());
+
+    session
+        .add_items(vec![json!("a"), json!({"b": [1, 2]})])
+        .await
+        .unwrap();
+    session.add_items(vec![]).await.unwrap();
+    session.add_items(vec![json!(3)]).await.unwrap();
+
+    assert_eq!(
+        session.get_items(None).await.unwrap(),
+        vec![json!("a"), json!({"b": [1, 2]}), json!(3)]
+    );
+    session.clear_session().await.unwrap();
+}
+
+/// A limit returns the newest items, still oldest first
+async fn check_limit(session: &dyn Session) {
+    session
+        .add_items((0..5).map(|i| json!(i)).collect())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        session.get_items(Some(2)).await.unwrap(),
+        vec![json!(3), json!(4)]
+    );
+    assert_eq!(session.get_items(Some(5)).await.unwrap().len(), 5);
+    assert_eq!(session.get_items(Some(50)).await.unwrap().len(), 5);
+    assert!(session.get_items(Some(0)).await.unwrap().is_empty());
+    session.clear_session().await.unwrap();
+}
+
+/// Popping removes and returns the newest item
+async fn check_pop_item(session: &dyn Session) {
+    assert_eq!(session.pop_item().await.unwrap(), None);
+
+    session.add_items(vec![json!(1), json!(2)]).await.unwrap();
+    assert_eq!(session.pop_item().await.unwrap(), Some(json!(2)));
+    session.add_items(vec![json!(3)]).await.unwrap();
+
+    assert_eq!(
+        session.get_items(None).await.unwrap(),
+        vec![json!(1), json!(3)]
+    );
+    session.clear_session().await.unwrap();
+}
+
+/// Clearing removes everything and the session stays usable
+async fn check_clear_session(session: &dyn Session) {
+    session.add_items(vec![json!(1)]).await.unwrap();
+    session.clear_session().await.unwrap();
+    session.clear_session().await.unwrap();
+    assert!(session.get_items(None).await.unwrap().is_empty());
+
+    session.add_items(vec![json!(2)]).await.unwrap();
+    assert_eq!(session.get_items(None).await.unwrap(), vec![json!(2)]);
+    session.clear_session().await.unwrap();
+}
+
+/// Replacing swaps the whole history, including for an empty one
+async fn check_replace_items(session: &dyn Session) {
+    session.add_items(vec![json!(1), json!(2)]).await.unwrap();
+    session
+        .replace_items(vec![json!("a"), json!("b"), json!("c")])
+        .await
+        .unwrap();
+    assert_eq!(
+        session.get_items(None).await.unwrap(),
+        vec![json!("a"), json!("b"), json!("c")]
+    );
+
+    session.add_items(vec![json!("d")]).await.unwrap();
+    assert_eq!(
+        session.get_items(Some(2)).await.unwrap(),
+        vec![json!("c"), json!("d")]
+    );
+
+    session.replace_items(vec![]).await.unwrap();
+    assert!(session.get_items(None).await.unwrap().is_empty());
+}
+
+/// Concurrent batches are each written whole and never interleaved
+async fn check_concurrent_writes(session: &dyn Session) {
+    let batches: Vec<Vec<_>> = (0..8)
+        .map(|batch| (0..3).map(|i| json!([batch, i])).collect())
+        .collect();
+    futures::future::try_join_all(batches.iter().map(|batch| session.add_items(batch.clone())))
+        .await
+        .unwrap();
+
+    let items = session.get_items(None).await.unwrap();
+    assert_eq!(items.len(), 24);
+    for chunk in items.chunks(3) {
+        assert!(batches.iter().any(|batch| batch == chunk), "{:?}", items);
+    }
+    session.clear_session().await.unwrap();
+}
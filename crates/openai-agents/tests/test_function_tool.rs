@@ -0,0 +1,182 @@
+//! Unit tests for the #[function_tool] macro
+
+use openai_agents::{AgentError, RunContext, Tool, function_tool};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct Location {
+    city: String,
+    country: Option<String>,
+}
+
+/// Count the given tags, up to a limit.
+#[function_tool]
+async fn count_tags(
+    tags: Vec<String>,
+    limit: u32,
+    location: Location,
+    origin: Option<Location>,
+    note: Option<String>,
+) -> usize {
+    let _ = (location, origin, note);
+    tags.len().min(limit as usize)
+}
+
+#[test]
+fn test_function_tool_typed_schema() {
+    let schema = COUNT_TAGSTool.parameters_schema();
+
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["additionalProperties"], json!(false));
+    assert_eq!(schema["properties"]["tags"]["type"], "array");
+    assert_eq!(schema["properties"]["tags"]["items"]["type"], "string");
+    assert_eq!(schema["properties"]["limit"]["type"], "integer");
+    assert!(schema["properties"]["limit"].get("format").is_none());
+
+    let location = &schema["properties"]["location"];
+    assert_eq!(location["type"], "object");
+    assert_eq!(location["additionalProperties"], json!(false));
+    assert_eq!(location["required"], json!(["city", "country"]));
+
+    // An optional struct is a nullable object, strict all the same
+    let origin = &schema["properties"]["origin"];
+    assert_eq!(origin["type"], json!(["object", "null"]));
+    assert_eq!(origin["additionalProperties"], json!(false));
+    assert_eq!(origin["required"], json!(["city", "country"]));
+
+    assert_eq!(
+        schema["properties"]["note"]["type"],
+        json!(["string", "null"])
+    );
+    assert_eq!(
+        schema["required"],
+        json!(["tags", "limit", "location", "origin", "note"])
+    );
+}
+
+#[tokio::test]
+async fn test_function_tool_optional_argument() {
+    let result = COUNT_TAGSTool
+        .execute(
+            &RunContext::default(),
+            json!({
+                "tags": ["a", "b", "c"],
+                "limit": 2,
+                "location": {"city": "Accra", "country": null},
+                "origin": null
+            }),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result, json!(2));
+}
+
+/// Ignored in favour of the attribute description.
+#[function_tool(
+    name = "lookup_weather",
+    description = "Look up the weather for a city."
+)]
+async fn weather(
+    /// The city to look up
+    city: String,
+    #[arg(rename = "unit", description = "Either C or F")] temperature_unit: Option<String>,
+) -> String {
+    format!("{} {}", city, temperature_unit.unwrap_or_default())
+}
+
+#[tokio::test]
+async fn test_function_tool_attributes() {
+    assert_eq!(WEATHERTool.name(), "lookup_weather");
+    assert_eq!(WEATHERTool.description(), "Look up the weather for a city.");
+
+    let schema = WEATHERTool.parameters_schema();
+    assert_eq!(
+        schema["properties"]["city"]["description"],
+        "The city to look up"
+    );
+    assert_eq!(schema["properties"]["unit"]["description"], "Either C or F");
+    assert!(schema["properties"].get("temperature_unit").is_none());
+    assert_eq!(schema["required"], json!(["city", "unit"]));
+
+    let result = WEATHERTool
+        .execute(
+            &RunContext::default(),
+            json!({"city": "Accra", "unit": "C"}),
+        )
+        .await
+        .unwrap();
+    assert_eq!(result, json!("Accra C"));
+}
+
+/// Divide two numbers.
+#[function_tool(timeout_ms = 250)]
+fn divide(a: f64, b: f64) -> std::result::Result<f64, String> {
+    if b == 0.0 {
+        Err("division by zero".to_string())
+    } else {
+        Ok(a / b)
+    }
+}
+
+#[tokio::test]
+async fn test_function_tool_sync_result() {
+    assert_eq!(DIVIDETool.timeout(), Some(Duration::from_millis(250)));
+    assert_eq!(GREETTool.timeout(), None);
+
+    let result = DIVIDETool
+        .execute(&RunContext::default(), json!({"a": 9.0, "b": 3.0}))
+        .await
+        .unwrap();
+    assert_eq!(result, json!(3.0));
+
+    let err = DIVIDETool
+        .execute(&RunContext::default(), json!({"a": 1.0, "b": 0.0}))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AgentError::ToolExecutionFailed { ref tool_name, ref reason }
+            if tool_name == "divide" && reason == "division by zero"
+    ));
+}
+
+struct AppState {
+    greeting: String,
+}
+
+/// Greet a user using the application's greeting.
+#[function_tool]
+async fn greet(ctx: &RunContext<AppState>, name: String) -> String {
+    format!(
+        "{} {} (call {})",
+        ctx.context().greeting,
+        name,
+        ctx.tool_call_id().unwrap_or_default()
+    )
+}
+
+#[tokio::test]
+async fn test_function_tool_run_context() {
+    let schema = GREETTool.parameters_schema();
+    assert_eq!(schema["required"], json!(["name"]));
+
+    let ctx = RunContext::new(AppState {
+        greeting: "Hello".to_string(),
+    })
+    .for_tool_call("call_1");
+    let result = GREETTool
+        .execute(&ctx, json!({"name": "Ama"}))
+        .await
+        .unwrap();
+    assert_eq!(result, json!("Hello Ama (call call_1)"));
+
+    let err = GREETTool
+        .execute(&RunContext::default(), json!({"name": "Ama"}))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AgentError::ConfigError(_)));
+}
@@ -0,0 +1,587 @@
+//! Integration tests for agent and tool guardrails
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use common::{ScriptedModel, text, tool_calls, with_usage};
+use futures::{StreamExt, stream};
+use openai_agents::models::{CompletionStream, StreamChunk};
+use openai_agents::{
+    Agent, AgentError, BlockMode, CompletionRequest, CompletionResponse, FunctionTool,
+    GuardrailEvaluation, GuardrailInterval, GuardrailKind, GuardrailOutput, GuardrailResult,
+    InputGuardrail, JudgeGuardrail, Message, ModelPrice, ModelProvider, OutputGuardrail,
+    PriceTable, RunConfig, RunContext, Runner, StreamEvent, ToolErrorPolicy, ToolInputGuardrail,
+    ToolOutputGuardrail,
+};
+use serde_json::{Value, json};
+
+/// Blocks any text mentioning a secret
+struct NoSecrets;
+
+#[async_trait]
+impl InputGuardrail for NoSecrets {
+    fn name(&self) -> &str {
+        "no_secrets"
+    }
+
+    async fn check(
+        &self,
+        _ctx: &RunContext,
+        input: &str,
+    ) -> openai_agents::Result<GuardrailOutput> {
+        Ok(if input.contains("secret") {
+            GuardrailResult::Block {
+                reason: "mentions a secret".to_string(),
+            }
+            .with_category("secrets")
+        } else {
+            GuardrailResult::Allow.into()
+        })
+    }
+}
+
+#[async_trait]
+impl OutputGuardrail for NoSecrets {
+    fn name(&self) -> &str {
+        "no_secrets"
+    }
+
+    async fn check(
+        &self,
+        ctx: &RunContext,
+        output: &str,
+    ) -> openai_agents::Result<GuardrailOutput> {
+        InputGuardrail::check(self, ctx, output).await
+    }
+}
+
+/// Replaces email addresses with a placeholder
+struct Redact;
+
+#[async_trait]
+impl InputGuardrail for Redact {
+    async fn check(
+        &self,
+        _ctx: &RunContext,
+        input: &str,
+    ) -> openai_agents::Result<GuardrailOutput> {
+        Ok(GuardrailResult::Modify {
+            new_content: input.replace("ama@example.com", "[email]"),
+        }
+        .into())
+    }
+}
+
+#[async_trait]
+impl OutputGuardrail for Redact {
+    async fn check(
+        &self,
+        ctx: &RunContext,
+        output: &str,
+    ) -> openai_agents::Result<GuardrailOutput> {
+        InputGuardrail::check(self, ctx, output).await
+    }
+}
+
+fn config(model: Arc<dyn ModelProvider>) -> RunConfig {
+    RunConfig {
+        model_override: Some(model),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_input_guardrail_blocks_before_model_call() {
+    let model = Arc::new(ScriptedModel::new(vec![text("unreachable")]));
+    let agent = Agent::builder("Test").input_guardrail(NoSecrets).build();
+
+    let err = Runner::run_with_config(&agent, "tell me the secret", config(model.clone()))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AgentError::InputGuardrailTriggered { ref guardrail, ref reason, ref category, .. }
+            if guardrail == "no_secrets"
+                && reason == "mentions a secret"
+                && category.as_deref() == Some("secrets")
+    ));
+    assert!(model.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_input_guardrail_checks_last_user_message() {
+    let model = Arc::new(ScriptedModel::new(vec![text("Noted")]));
+    let agent = Agent::builder("Test").input_guardrail(Redact).build();
+    let input = vec![
+        Message::user("ama@example.com wrote earlier"),
+        Message::assistant("Hello"),
+        Message::user("I am ama@example.com"),
+    ];
+
+    Runner::run_with_config(&agent, input, config(model.clone()))
+        .await
+        .unwrap();
+    assert_eq!(
+        model.requests()[0].messages,
+        vec![
+            Message::user("ama@example.com wrote earlier"),
+            Message::assistant("Hello"),
+            Message::user("I am [email]"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_input_guardrail_modifies_input() {
+    let model = Arc::new(ScriptedModel::new(vec![text("Noted")]));
+    let agent = Agent::builder("Test").input_guardrail(Redact).build();
+
+    let result = Runner::run_with_config(&agent, "I am ama@example.com", config(model.clone()))
+        .await
+        .unwrap();
+    assert_eq!(result.final_output(), "Noted");
+    assert_eq!(
+        model.requests()[0].messages.last().unwrap(),
+        &Message::user("I am [email]")
+    );
+}
+
+#[tokio::test]
+async fn test_output_guardrails_block_and_modify() {
+    let model = Arc::new(ScriptedModel::new(vec![text("Write to ama@example.com")]));
+    let agent = Agent::builder("Test").output_guardrail(Redact).build();
+    let result = Runner::run_with_config(&agent, "Contact?", config(model))
+        .await
+        .unwrap();
+    assert_eq!(result.final_output(), "Write to [email]");
+
+    let model = Arc::new(ScriptedModel::new(vec![text("The secret is 42")]));
+    let agent = Agent::builder("Test").output_guardrail(NoSecrets).build();
+    let result = Runner::run_streamed_with_config(&agent, "Hi", config(model))
+        .await
+        .unwrap();
+    let err = result.final_result().await.unwrap_err();
+    assert!(matches!(
+        err,
+        AgentError::OutputGuardrailTriggered { ref guardrail, .. } if guardrail == "no_secrets"
+    ));
+}
+
+/// Trips immediately while running alongside the model call
+struct ParallelNoSecrets;
+
+#[async_trait]
+impl InputGuardrail for ParallelNoSecrets {
+    fn run_in_parallel(&self) -> bool {
+        true
+    }
+
+    async fn check(&self, ctx: &RunContext, input: &str) -> openai_agents::Result<GuardrailOutput> {
+        InputGuardrail::check(&NoSecrets, ctx, input).await
+    }
+}
+
+/// A model that never answers
+struct HangingModel;
+
+#[async_trait]
+impl ModelProvider for HangingModel {
+    async fn complete(
+        &self,
+        _request: CompletionRequest,
+    ) -> openai_agents::Result<CompletionResponse> {
+        futures::future::pending().await
+    }
+
+    async fn stream(&self, _request: CompletionRequest) -> openai_agents::Result<CompletionStream> {
+        futures::future::pending().await
+    }
+}
+
+#[tokio::test]
+async fn test_parallel_input_guardrail_cancels_model_call() {
+    let agent = Agent::builder("Test")
+        .input_guardrail(ParallelNoSecrets)
+        .build();
+
+    let run = Runner::run_with_config(&agent, "tell me the secret", config(Arc::new(HangingModel)));
+    let err = tokio::time::timeout(Duration::from_secs(5), run)
+        .await
+        .expect("the model call should be cancelled")
+        .unwrap_err();
+    assert!(matches!(err, AgentError::InputGuardrailTriggered { .. }));
+}
+
+fn echo_tool() -> FunctionTool {
+    FunctionTool::new(
+        "echo",
+        "Echo the text back",
+        json!({"type": "object", "properties": {"text": {"type": "string"}}}),
+        |args| async move { Ok(args["text"].clone()) },
+    )
+}
+
+/// Upper-cases the `text` argument of every call
+struct Shout;
+
+#[async_trait]
+impl ToolInputGuardrail for Shout {
+    async fn check(
+        &self,
+        _ctx: &RunContext,
+        _tool_name: &str,
+        input: &Value,
+    ) -> openai_agents::Result<GuardrailOutput> {
+        let text = input["text"].as_str().unwrap_or_default().to_uppercase();
+        Ok(GuardrailResult::Modify {
+            new_content: json!({ "text": text }).to_string(),
+        }
+        .into())
+    }
+}
+
+/// Blocks tool arguments or outputs mentioning a secret
+struct NoSecretTools {
+    block_mode: BlockMode,
+}
+
+impl NoSecretTools {
+    fn check_value(&self, value: &Value) -> GuardrailResult {
+        if value.to_string().to_lowercase().contains("secret") {
+            GuardrailResult::Block {
+                reason: "mentions a secret".to_string(),
+            }
+        } else {
+            GuardrailResult::Allow
+        }
+    }
+}
+
+#[async_trait]
+impl ToolInputGuardrail for NoSecretTools {
+    fn name(&self) -> &str {
+        "no_secret_tools"
+    }
+
+    fn block_mode(&self) -> BlockMode {
+        self.block_mode
+    }
+
+    async fn check(
+        &self,
+        _ctx: &RunContext,
+        _tool_name: &str,
+        input: &Value,
+    ) -> openai_agents::Result<GuardrailOutput> {
+        Ok(self.check_value(input).into())
+    }
+}
+
+#[async_trait]
+impl ToolOutputGuardrail for NoSecretTools {
+    fn name(&self) -> &str {
+        "no_secret_tools"
+    }
+
+    fn block_mode(&self) -> BlockMode {
+        self.block_mode
+    }
+
+    async fn check(
+        &self,
+        _ctx: &RunContext,
+        _tool_name: &str,
+        output: &Value,
+    ) -> openai_agents::Result<GuardrailOutput> {
+        Ok(self.check_value(output).into())
+    }
+}
+
+#[tokio::test]
+async fn test_guardrail_evaluations_are_reported() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[("call_1", "echo", json!({"text": "the secret"}))]),
+        text("Done"),
+    ]));
+    let tool = echo_tool().with_output_guardrail(NoSecretTools {
+        block_mode: BlockMode::RejectContent,
+    });
+    let agent = Agent::builder("Test")
+        .tool(tool)
+        .input_guardrail(NoSecrets)
+        .output_guardrail(NoSecrets)
+        .build();
+
+    let result = Runner::run_with_config(&agent, "Hi", config(model))
+        .await
+        .unwrap();
+    let evaluations: Vec<_> = result
+        .guardrail_evaluations()
+        .iter()
+        .map(|e: &GuardrailEvaluation| {
+            (
+                e.kind,
+                e.guardrail.as_str(),
+                e.tool_name.as_deref(),
+                e.tripped(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        evaluations,
+        vec![
+            (GuardrailKind::Input, "no_secrets", None, false),
+            (
+                GuardrailKind::ToolOutput,
+                "no_secret_tools",
+                Some("echo"),
+                true
+            ),
+            (GuardrailKind::Output, "no_secrets", None, false),
+        ]
+    );
+    assert!(
+        result
+            .guardrail_evaluations()
+            .iter()
+            .all(|e| e.agent_name == "Test")
+    );
+}
+
+#[tokio::test]
+async fn test_tool_guardrails_modify_then_reject_content() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[("call_1", "echo", json!({"text": "the secret"}))]),
+        text("Done"),
+    ]));
+    let tool = echo_tool().with_output_guardrail(NoSecretTools {
+        block_mode: BlockMode::RejectContent,
+    });
+    let agent = Agent::builder("Test")
+        .tool(tool)
+        .tool_input_guardrail(Shout)
+        .build();
+
+    let result = Runner::run_with_config(&agent, "Hi", config(model.clone()))
+        .await
+        .unwrap();
+    assert_eq!(result.final_output(), "Done");
+
+    // The output guardrail sees the modified arguments echoed back
+    let requests = model.requests();
+    let Message::Tool { content, .. } = requests[1].messages.last().unwrap() else {
+        panic!("expected a tool result");
+    };
+    assert!(content.contains("rejected by guardrail 'no_secret_tools'"));
+}
+
+#[tokio::test]
+async fn test_run_tool_guardrail_aborts_despite_error_policy() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[("call_1", "echo", json!({"text": "secret"}))]),
+        text("unreachable"),
+    ]));
+    let agent = Agent::builder("Test").tool(echo_tool()).build();
+    let config = RunConfig {
+        model_override: Some(model),
+        tool_error_policy: ToolErrorPolicy::ReturnToModel,
+        tool_input_guardrails: vec![Arc::new(NoSecretTools {
+            block_mode: BlockMode::Abort,
+        })],
+        ..Default::default()
+    };
+
+    let result = Runner::run_streamed_with_config(&agent, "Hi", config)
+        .await
+        .unwrap();
+    let err = result.final_result().await.unwrap_err();
+    assert!(matches!(
+        err,
+        AgentError::ToolInputGuardrailTriggered { ref guardrail, ref tool_name, .. }
+            if guardrail == "no_secret_tools" && tool_name == "echo"
+    ));
+}
+
+#[tokio::test]
+async fn test_judge_guardrail_verdicts() {
+    let judge_model = Arc::new(ScriptedModel::new(vec![
+        text(r#"{"tripwire_triggered": false, "reasoning": "harmless greeting"}"#),
+        text(r#"{"tripwire_triggered": true, "reasoning": "recommends a dosage"}"#),
+    ]));
+    let guardrail = JudgeGuardrail::new("no_medical_advice", "Never give medical advice.")
+        .model("judge-model")
+        .model_provider(judge_model.clone());
+    let model = Arc::new(ScriptedModel::new(vec![text("Take two pills")]));
+    let agent = Agent::builder("Test")
+        .input_guardrail(guardrail.clone())
+        .output_guardrail(guardrail)
+        .build();
+
+    let err = Runner::run_with_config(&agent, "Hello", config(model))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AgentError::OutputGuardrailTriggered { ref guardrail, ref reason, ref output_info, .. }
+            if guardrail == "no_medical_advice"
+                && reason == "recommends a dosage"
+                && output_info.as_ref().unwrap()["tripwire_triggered"] == json!(true)
+    ));
+
+    // The judge sees the content as input and answers with a verdict
+    let requests = judge_model.requests();
+    assert_eq!(requests[0].model, "judge-model");
+    assert_eq!(
+        requests[0].messages.last().unwrap(),
+        &Message::user("Hello")
+    );
+    assert_eq!(
+        requests[1].messages.last().unwrap(),
+        &Message::user("Take two pills")
+    );
+    assert!(requests[0].response_format.is_some());
+}
+
+#[tokio::test]
+async fn test_judge_usage_counts_towards_run() {
+    let allow = || {
+        with_usage(
+            text(r#"{"tripwire_triggered": false, "reasoning": "fine"}"#),
+            100_000,
+            0,
+        )
+    };
+    let prices = Arc::new(
+        PriceTable::new()
+            .with_price("main-model", ModelPrice::new(1.0, 0.0))
+            .with_price("judge-model", ModelPrice::new(1.0, 0.0)),
+    );
+
+    for max_cost in [None, Some(0.25)] {
+        let judge_model = Arc::new(ScriptedModel::new(vec![allow(), allow()]));
+        let guardrail = JudgeGuardrail::new("policy", "Be polite.")
+            .model("judge-model")
+            .model_provider(judge_model);
+        let model = Arc::new(ScriptedModel::new(vec![with_usage(
+            text("Hello there"),
+            100_000,
+            0,
+        )]));
+        let agent = Agent::builder("Test")
+            .model("main-model")
+            .input_guardrail(guardrail.clone())
+            .output_guardrail(guardrail)
+            .build();
+        let config = RunConfig {
+            price_table: Some(prices.clone()),
+            max_cost,
+            ..config(model)
+        };
+
+        let streamed = Runner::run_streamed_with_config(&agent, "Hi", config)
+            .await
+            .unwrap();
+        let result = streamed.final_result().await;
+
+        // Both verdicts are accounted in the run, and the budget sees them
+        assert_eq!(streamed.usage().requests, 3);
+        assert_eq!(streamed.usage_by_agent()["policy judge"].requests, 2);
+        assert!((streamed.cost().unwrap() - 0.3).abs() < 1e-9);
+        match max_cost {
+            None => assert_eq!(result.unwrap().usage().requests, 3),
+            Some(_) => assert!(matches!(
+                result.unwrap_err(),
+                AgentError::BudgetExceeded { limit, spent }
+                    if limit == 0.25 && (spent - 0.3).abs() < 1e-9
+            )),
+        }
+    }
+}
+
+/// A model streaming a fixed answer in the given deltas
+struct DeltaModel {
+    deltas: Vec<&'static str>,
+}
+
+#[async_trait]
+impl ModelProvider for DeltaModel {
+    async fn complete(
+        &self,
+        _request: CompletionRequest,
+    ) -> openai_agents::Result<CompletionResponse> {
+        Err(AgentError::ModelError("the runner always streams".into()))
+    }
+
+    async fn stream(&self, _request: CompletionRequest) -> openai_agents::Result<CompletionStream> {
+        let chunks: Vec<_> = self
+            .deltas
+            .iter()
+            .map(|delta| {
+                Ok(StreamChunk {
+                    delta: Some(delta.to_string()),
+                    tool_call_deltas: vec![],
+                    finish_reason: None,
+                    usage: None,
+                })
+            })
+            .collect();
+        Ok(CompletionStream::new(stream::iter(chunks).boxed()))
+    }
+}
+
+/// Collect the streamed text and tripped guardrails of a run, and its error
+async fn stream_with_interval(
+    interval: GuardrailInterval,
+    deltas: Vec<&'static str>,
+) -> (String, Vec<String>, AgentError) {
+    let agent = Agent::builder("Test").output_guardrail(NoSecrets).build();
+    let config = RunConfig {
+        model_override: Some(Arc::new(DeltaModel { deltas })),
+        output_guardrail_interval: Some(interval),
+        ..Default::default()
+    };
+
+    let result = Runner::run_streamed_with_config(&agent, "Hi", config)
+        .await
+        .unwrap();
+    let mut streamed = String::new();
+    let mut tripped = Vec::new();
+    let mut events = result.stream_events();
+    while let Some(event) = events.next().await {
+        match event {
+            StreamEvent::RawResponse(raw) => streamed.push_str(&raw.data),
+            StreamEvent::GuardrailTripped(event) => tripped.push(event.guardrail),
+            _ => {}
+        }
+    }
+    (streamed, tripped, result.final_result().await.unwrap_err())
+}
+
+#[tokio::test]
+async fn test_streaming_guardrail_cuts_off_at_sentence() {
+    let (streamed, tripped, err) = stream_with_interval(
+        GuardrailInterval::Sentence,
+        vec!["Hello ", "there. The ", "secret is", " 42. Bye."],
+    )
+    .await;
+
+    assert_eq!(streamed, "Hello there.");
+    assert_eq!(tripped, vec!["no_secrets"]);
+    assert!(matches!(err, AgentError::OutputGuardrailTriggered { .. }));
+}
+
+#[tokio::test]
+async fn test_streaming_guardrail_every_chars() {
+    let (streamed, _, _) = stream_with_interval(
+        GuardrailInterval::EveryChars(4),
+        vec!["ab", "cd", "ef", " secret"],
+    )
+    .await;
+
+    // "abcd" was released; "ef" was still held back when the secret arrived
+    assert_eq!(streamed, "abcd");
+}
@@ -0,0 +1,207 @@
+//! Integration tests for per-agent turn, token and tool call limits
+
+mod common;
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use common::{ScriptedModel, text, tool_calls, with_usage};
+use openai_agents::{
+    Agent, AgentBuilder, AgentError, CompletionResponse, FunctionTool, Message, RunConfig, Runner,
+    ToolChoice,
+};
+use serde_json::json;
+
+/// A tool counting how often it runs
+fn counting_tool(calls: Arc<AtomicUsize>) -> FunctionTool {
+    FunctionTool::new_sync(
+        "count",
+        "Count a call",
+        json!({"type": "object", "properties": {}}),
+        move |_args| Ok(json!(calls.fetch_add(1, Ordering::SeqCst) + 1)),
+    )
+}
+
+fn call(id: &str) -> CompletionResponse {
+    tool_calls(&[(id, "count", json!({}))])
+}
+
+fn worker(calls: &Arc<AtomicUsize>) -> AgentBuilder {
+    Agent::builder("Worker").tool(counting_tool(calls.clone()))
+}
+
+fn config(model: &Arc<ScriptedModel>) -> RunConfig {
+    RunConfig {
+        model_override: Some(model.clone()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_agent_max_turns() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        call("call_1"),
+        call("call_2"),
+        text("unreachable"),
+    ]));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let agent = worker(&calls).max_turns(2).build();
+
+    let err = Runner::run_with_config(&agent, "Go", config(&model))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AgentError::AgentMaxTurnsExceeded { ref agent, limit: 2 } if agent == "Worker"
+    ));
+    assert_eq!(model.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_final_answer_at_turn_limit() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        call("call_1"),
+        text("Best effort"),
+    ]));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let agent = worker(&calls)
+        .max_turns(1)
+        .final_answer_on_limit(true)
+        .build();
+
+    let result = Runner::run_with_config(&agent, "Go", config(&model))
+        .await
+        .unwrap();
+    assert_eq!(result.final_output(), "Best effort");
+
+    let requests = model.requests();
+    assert_eq!(requests[0].tool_choice, None);
+    assert_eq!(requests[1].tool_choice, Some(ToolChoice::None));
+}
+
+#[tokio::test]
+async fn test_final_answer_at_run_max_turns() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        call("call_1"),
+        text("Best effort"),
+    ]));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let agent = worker(&calls).final_answer_on_limit(true).build();
+    let config = RunConfig {
+        max_turns: 1,
+        ..config(&model)
+    };
+
+    let result = Runner::run_with_config(&agent, "Go", config).await.unwrap();
+    assert_eq!(result.final_output(), "Best effort");
+}
+
+#[tokio::test]
+async fn test_max_tool_calls() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        call("call_1"),
+        tool_calls(&[
+            ("call_2", "count", json!({})),
+            ("call_3", "count", json!({})),
+        ]),
+        text("unreachable"),
+    ]));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let agent = worker(&calls).max_tool_calls(2).build();
+
+    let err = Runner::run_with_config(&agent, "Go", config(&model))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AgentError::MaxToolCallsExceeded { limit: 2, .. }
+    ));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_final_answer_at_tool_call_limit() {
+    let mut rejected = call("call_2");
+    rejected.content = Some("Counting again".to_string());
+    let model = Arc::new(ScriptedModel::new(vec![
+        call("call_1"),
+        rejected,
+        text("Best effort"),
+    ]));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let agent = worker(&calls)
+        .max_tool_calls(1)
+        .final_answer_on_limit(true)
+        .build();
+
+    let result = Runner::run_with_config(&agent, "Go", config(&model))
+        .await
+        .unwrap();
+    assert_eq!(result.final_output(), "Best effort");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // The call over the limit is left out of the transcript
+    let last = model.requests().pop().unwrap();
+    assert_eq!(last.tool_choice, Some(ToolChoice::None));
+    assert_eq!(last.messages.last().unwrap().role(), "tool");
+
+    // And out of the history of the run, which matches the transcript
+    let items: Vec<_> = result
+        .new_items()
+        .iter()
+        .map(|item| serde_json::to_value(item).unwrap()["type"].clone())
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            json!("tool_call"),
+            json!("tool_output"),
+            json!("message_output")
+        ]
+    );
+    assert_eq!(result.raw_responses().len(), 2);
+    let mut transcript = last.messages.clone();
+    transcript.push(Message::assistant("Best effort"));
+    assert_eq!(result.to_input_list(), transcript);
+    assert_eq!(result.usage().requests, 3);
+}
+
+#[tokio::test]
+async fn test_token_limits() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let model = Arc::new(ScriptedModel::new(vec![
+        with_usage(call("call_1"), 100, 40),
+        with_usage(call("call_2"), 100, 40),
+        text("unreachable"),
+    ]));
+    let agent = worker(&calls).max_output_tokens(50).build();
+    let err = Runner::run_with_config(&agent, "Go", config(&model))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AgentError::MaxOutputTokensExceeded {
+            limit: 50,
+            used: 80,
+            ..
+        }
+    ));
+
+    let model = Arc::new(ScriptedModel::new(vec![
+        with_usage(call("call_1"), 100, 40),
+        text("unreachable"),
+    ]));
+    let agent = worker(&calls).max_total_tokens(100).build();
+    let err = Runner::run_with_config(&agent, "Go", config(&model))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AgentError::MaxTotalTokensExceeded {
+            limit: 100,
+            used: 140,
+            ..
+        }
+    ));
+}
@@ -0,0 +1,143 @@
+//! Integration tests for cost estimation and run budgets
+
+mod common;
+
+use std::sync::Arc;
+
+use common::{ScriptedModel, text, tool_calls, with_usage};
+use openai_agents::{
+    Agent, AgentError, FunctionTool, ModelPrice, PriceTable, RunConfig, Runner, Usage,
+};
+use serde_json::json;
+
+fn prices() -> PriceTable {
+    PriceTable::new().with_price(
+        "test-model",
+        ModelPrice::new(2.0, 8.0).with_cached_input(0.5),
+    )
+}
+
+#[test]
+fn test_model_price_cost() {
+    let usage = Usage {
+        requests: 1,
+        prompt_tokens: 1_000_000,
+        completion_tokens: 500_000,
+        cached_tokens: 400_000,
+        total_tokens: 1_500_000,
+        ..Default::default()
+    };
+
+    // 600k uncached at $2, 400k cached at $0.50, 500k output at $8
+    let cost = prices().cost("test-model", &usage).unwrap();
+    assert!((cost - 5.4).abs() < 1e-9);
+    assert_eq!(prices().cost("other-model", &usage), None);
+
+    // Without a cached price, cached tokens cost as much as other input
+    let cost = ModelPrice::new(2.0, 8.0).cost(&usage);
+    assert!((cost - 6.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_price_table_formats() {
+    let from_json = PriceTable::from_json(
+        r#"{"test-model": {"input": 2.0, "cached_input": 0.5, "output": 8.0}}"#,
+    )
+    .unwrap();
+    let from_toml = PriceTable::from_toml(
+        r#"
+        [test-model]
+        input = 2.0
+        cached_input = 0.5
+        output = 8.0
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(from_json, prices());
+    assert_eq!(from_toml, prices());
+    assert!(matches!(
+        PriceTable::from_toml("[test-model]\ninput = 'free'"),
+        Err(AgentError::SerializationError(_))
+    ));
+}
+
+fn noop_tool() -> FunctionTool {
+    FunctionTool::new_sync(
+        "noop",
+        "Do nothing",
+        json!({"type": "object", "properties": {}}),
+        |_args| Ok(json!("ok")),
+    )
+}
+
+#[tokio::test]
+async fn test_run_cost_per_call() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        with_usage(tool_calls(&[("call_1", "noop", json!({}))]), 100_000, 0),
+        with_usage(text("Done"), 200_000, 50_000),
+    ]));
+    let agent = Agent::builder("Test")
+        .model("test-model")
+        .tool(noop_tool())
+        .build();
+    let config = RunConfig {
+        model_override: Some(model),
+        price_table: Some(Arc::new(prices())),
+        ..Default::default()
+    };
+
+    let result = Runner::run_with_config(&agent, "Hi", config).await.unwrap();
+    let costs: Vec<_> = result.model_calls().iter().map(|c| c.cost).collect();
+    assert_eq!(costs, vec![Some(0.2), Some(0.8)]);
+    assert!((result.cost().unwrap() - 1.0).abs() < 1e-9);
+    assert_eq!(result.model_calls()[0].model, "test-model");
+}
+
+#[tokio::test]
+async fn test_budget_stops_run() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        with_usage(tool_calls(&[("call_1", "noop", json!({}))]), 300_000, 0),
+        with_usage(tool_calls(&[("call_2", "noop", json!({}))]), 300_000, 0),
+        text("unreachable"),
+    ]));
+    let agent = Agent::builder("Test")
+        .model("test-model")
+        .tool(noop_tool())
+        .build();
+    let config = RunConfig {
+        model_override: Some(model.clone()),
+        price_table: Some(Arc::new(prices())),
+        max_cost: Some(1.0),
+        ..Default::default()
+    };
+
+    let streamed = Runner::run_streamed_with_config(&agent, "Hi", config)
+        .await
+        .unwrap();
+    let err = streamed.final_result().await.unwrap_err();
+    assert!(matches!(
+        err,
+        AgentError::BudgetExceeded { limit, spent } if limit == 1.0 && (spent - 1.2).abs() < 1e-9
+    ));
+    assert_eq!(model.requests().len(), 2);
+    assert!((streamed.cost().unwrap() - 1.2).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_budget_requires_price() {
+    let model = Arc::new(ScriptedModel::new(vec![text("unreachable")]));
+    let agent = Agent::builder("Test").model("unpriced-model").build();
+    let config = RunConfig {
+        model_override: Some(model.clone()),
+        price_table: Some(Arc::new(prices())),
+        max_cost: Some(1.0),
+        ..Default::default()
+    };
+
+    let err = Runner::run_with_config(&agent, "Hi", config)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, AgentError::ConfigError(_)));
+    assert!(model.requests().is_empty());
+}
@@ -0,0 +1,178 @@
+//! Integration tests for the Redis session
+//!
+//! These need a Redis server, so they are ignored by default; run them with
+//! `cargo test --features redis-session --test test_redis_session --
+//! --include-ignored`. They use `REDIS_URL` if set, or start a
+//! `redis-server` binary from the `PATH` on a free port, and fail when
+//! neither is available.
+
+#![cfg(feature = "redis-session")]
+
+mod common;
+
+use std::net::TcpListener;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use common::session::check_session;
+use openai_agents::{RedisSession, Session};
+use serde_json::json;
+
+/// A Redis server to test against, stopped on drop if we started it
+///
+/// Sessions of one test share a key prefix unique to the test.
+struct TestRedis {
+    url: String,
+    key_prefix: String,
+    child: Option<Child>,
+}
+
+impl TestRedis {
+    async fn start() -> Self {
+        Self::connect()
+            .await
+            .unwrap_or_else(|| panic!("No Redis server: set REDIS_URL or install redis-server"))
+    }
+
+    async fn connect() -> Option<Self> {
+        let key_prefix = format!("test:{}", uuid::Uuid::new_v4());
+        if let Ok(url) = std::env::var("REDIS_URL") {
+            return Some(Self {
+                url,
+                key_prefix,
+                child: None,
+            });
+        }
+
+        let port = TcpListener::bind("127.0.0.1:0")
+            .ok()?
+            .local_addr()
+            .ok()?
+            .port();
+        let child = Command::new("redis-server")
+            .args([
+                "--port",
+                &port.to_string(),
+                "--save",
+                "",
+                "--appendonly",
+                "no",
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let redis = Self {
+            url: format!("redis://127.0.0.1:{}/", port),
+            key_prefix,
+            child: Some(child),
+        };
+
+        for _ in 0..50 {
+            if tokio::net::TcpStream::connect(("127.0.0.1", port))
+                .await
+                .is_ok()
+            {
+                return Some(redis);
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        None
+    }
+
+    async fn session(&self, session_id: &str) -> RedisSession {
+        RedisSession::new(session_id, &self.url)
+            .await
+            .unwrap()
+            .with_key_prefix(self.key_prefix.clone())
+    }
+}
+
+impl Drop for TestRedis {
+    fn drop(&mut self) {
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs a Redis server (REDIS_URL or redis-server)"]
+async fn test_redis_session_conformance() {
+    let redis = TestRedis::start().await;
+    check_session(&redis.session("user_1").await).await;
+}
+
+#[tokio::test]
+#[ignore = "needs a Redis server (REDIS_URL or redis-server)"]
+async fn test_redis_session_items() {
+    let redis = TestRedis::start().await;
+    let session = redis.session("user_1").await;
+
+    session
+        .add_items(vec![json!(1), json!(2), json!(3)])
+        .await
+        .unwrap();
+    session.add_items(vec![]).await.unwrap();
+    session.add_items(vec![json!({"n": 4})]).await.unwrap();
+
+    assert_eq!(
+        session.get_items(None).await.unwrap(),
+        vec![json!(1), json!(2), json!(3), json!({"n": 4})]
+    );
+    assert_eq!(
+        session.get_items(Some(2)).await.unwrap(),
+        vec![json!(3), json!({"n": 4})]
+    );
+    assert_eq!(session.get_items(Some(10)).await.unwrap().len(), 4);
+    assert!(session.get_items(Some(0)).await.unwrap().is_empty());
+
+    assert_eq!(session.pop_item().await.unwrap(), Some(json!({"n": 4})));
+    assert_eq!(session.get_items(None).await.unwrap().len(), 3);
+
+    session.clear_session().await.unwrap();
+    assert!(session.get_items(None).await.unwrap().is_empty());
+    assert_eq!(session.pop_item().await.unwrap(), None);
+}
+
+#[tokio::test]
+#[ignore = "needs a Redis server (REDIS_URL or redis-server)"]
+async fn test_redis_sessions_are_isolated() {
+    let redis = TestRedis::start().await;
+    let first = redis.session("user_1").await;
+    let second = redis.session("user_2").await;
+    let reopened = redis.session("user_1").await;
+    let other_prefix = redis
+        .session("user_1")
+        .await
+        .with_key_prefix(format!("{}:other", redis.key_prefix));
+
+    first.add_items(vec![json!("first")]).await.unwrap();
+    second.add_items(vec![json!("second")]).await.unwrap();
+    assert_eq!(
+        reopened.get_items(None).await.unwrap(),
+        vec![json!("first")]
+    );
+    assert!(other_prefix.get_items(None).await.unwrap().is_empty());
+
+    first.clear_session().await.unwrap();
+    assert!(reopened.get_items(None).await.unwrap().is_empty());
+    assert_eq!(second.get_items(None).await.unwrap(), vec![json!("second")]);
+}
+
+#[tokio::test]
+#[ignore = "needs a Redis server (REDIS_URL or redis-server)"]
+async fn test_redis_session_ttl() {
+    let redis = TestRedis::start().await;
+    let session = redis
+        .session("user_1")
+        .await
+        .with_ttl(Duration::from_millis(200));
+
+    session.add_items(vec![json!(1)]).await.unwrap();
+    assert_eq!(session.get_items(None).await.unwrap(), vec![json!(1)]);
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert!(session.get_items(None).await.unwrap().is_empty());
+}
@@ -0,0 +1,886 @@
+//! Integration tests for the runner using a scripted model
+
+mod common;
+
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use common::{ScriptedModel, text, tool_calls, with_usage};
+use futures::StreamExt;
+use openai_agents::{
+    Agent, AgentError, AgentHooks, CompletionRequest, FunctionTool, Handoff, InMemorySession,
+    Message, RunConfig, RunContext, RunItem, Runner, Session, StreamEvent, ToolCall,
+    ToolErrorPolicy, Usage,
+};
+use serde_json::{Value, json};
+
+fn flaky_tool() -> FunctionTool {
+    FunctionTool::new_sync(
+        "flaky",
+        "A tool that always fails",
+        json!({"type": "object", "properties": {}}),
+        |_args| Err(AgentError::tool_failed("flaky", "service unavailable")),
+    )
+}
+
+#[tokio::test]
+async fn test_tool_error_aborts_by_default() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[("call_1", "flaky", json!({}))]),
+        text("unreachable"),
+    ]));
+    let agent = Agent::builder("Test").tool(flaky_tool()).build();
+    let config = RunConfig {
+        model_override: Some(model),
+        ..Default::default()
+    };
+
+    let err = Runner::run_with_config(&agent, "Hi", config)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AgentError::ToolExecutionFailed { ref reason, .. } if reason == "service unavailable"
+    ));
+}
+
+#[tokio::test]
+async fn test_tool_error_returned_to_model() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[("call_1", "flaky", json!({}))]),
+        text("Recovered"),
+    ]));
+    let agent = Agent::builder("Test").tool(flaky_tool()).build();
+    let config = RunConfig {
+        model_override: Some(model.clone()),
+        tool_error_policy: ToolErrorPolicy::ReturnToModel,
+        ..Default::default()
+    };
+
+    let result = Runner::run_with_config(&agent, "Hi", config).await.unwrap();
+    assert_eq!(result.final_output(), "Recovered");
+
+    let requests = model.requests();
+    let tool_message = requests[1].messages.last().unwrap();
+    assert!(
+        tool_message
+            .content()
+            .unwrap()
+            .contains("service unavailable")
+    );
+}
+
+#[tokio::test]
+async fn test_tool_error_formatter_in_streaming() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[("call_1", "flaky", json!({}))]),
+        text("Recovered"),
+    ]));
+    let agent = Agent::builder("Test")
+        .tool(flaky_tool())
+        .tool_error_policy(ToolErrorPolicy::format(|tool_name, _error| {
+            format!("{} is down, answer without it", tool_name)
+        }))
+        .build();
+    let config = RunConfig {
+        model_override: Some(model),
+        ..Default::default()
+    };
+
+    let result = Runner::run_streamed_with_config(&agent, "Hi", config)
+        .await
+        .unwrap();
+    let mut events = result.stream_events();
+    let mut tool_output = None;
+    while let Some(event) = events.next().await {
+        if let StreamEvent::RunItem(item_event) = event {
+            if let RunItem::ToolOutput { output, .. } = item_event.item {
+                tool_output = Some(output);
+            }
+        }
+    }
+
+    let tool_output: Value = serde_json::from_str(&tool_output.unwrap()).unwrap();
+    assert_eq!(tool_output, json!("flaky is down, answer without it"));
+    assert_eq!(result.final_output().await.unwrap(), "Recovered");
+}
+
+#[tokio::test]
+async fn test_tool_error_aborts_streaming_run() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[("call_1", "missing_tool", json!({}))]),
+        text("unreachable"),
+    ]));
+    let agent = Agent::builder("Test").build();
+    let config = RunConfig {
+        model_override: Some(model),
+        ..Default::default()
+    };
+
+    let result = Runner::run_streamed_with_config(&agent, "Hi", config)
+        .await
+        .unwrap();
+    let err = result.final_result().await.unwrap_err();
+    assert!(matches!(
+        err,
+        AgentError::ToolExecutionFailed { ref tool_name, .. } if tool_name == "missing_tool"
+    ));
+}
+
+/// A tool that sleeps and records the highest number of concurrent executions
+fn slow_tool(
+    name: &'static str,
+    in_flight: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+) -> FunctionTool {
+    FunctionTool::new(
+        name,
+        "A slow tool",
+        json!({"type": "object", "properties": {}}),
+        move |_args| {
+            let in_flight = in_flight.clone();
+            let peak = peak.clone();
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(json!(name))
+            }
+        },
+    )
+}
+
+async fn run_slow_tools(parallel: bool) -> (usize, Arc<ScriptedModel>) {
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[
+            ("call_1", "first", json!({})),
+            ("call_2", "second", json!({})),
+        ]),
+        text("Done"),
+    ]));
+    let agent = Agent::builder("Test")
+        .tool(slow_tool("first", in_flight.clone(), peak.clone()))
+        .tool(slow_tool("second", in_flight, peak.clone()))
+        .parallel_tool_calls(parallel)
+        .build();
+    let config = RunConfig {
+        model_override: Some(model.clone()),
+        ..Default::default()
+    };
+
+    let result = Runner::run_with_config(&agent, "Hi", config).await.unwrap();
+    assert_eq!(result.final_output(), "Done");
+
+    (peak.load(Ordering::SeqCst), model)
+}
+
+#[tokio::test]
+async fn test_parallel_tool_calls_run_concurrently() {
+    let (peak, model) = run_slow_tools(true).await;
+    assert_eq!(peak, 2);
+
+    let requests = model.requests();
+    assert_eq!(requests[0].parallel_tool_calls, Some(true));
+
+    // Results keep the order of the tool calls
+    let messages = &requests[1].messages;
+    let outputs: Vec<_> = messages[messages.len() - 2..]
+        .iter()
+        .map(|m| m.content().unwrap().to_string())
+        .collect();
+    assert!(outputs[0].contains("first"));
+    assert!(outputs[1].contains("second"));
+}
+
+#[tokio::test]
+async fn test_sequential_tool_calls_when_parallel_disabled() {
+    let (peak, model) = run_slow_tools(false).await;
+    assert_eq!(peak, 1);
+    assert_eq!(model.requests()[0].parallel_tool_calls, Some(false));
+}
+
+fn hanging_tool() -> FunctionTool {
+    FunctionTool::new(
+        "hang",
+        "A tool that never answers in time",
+        json!({"type": "object", "properties": {}}),
+        |_args| async {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            Ok(json!("too late"))
+        },
+    )
+}
+
+/// Records the tool hooks called, with the id of the call they were given
+#[derive(Clone, Default)]
+struct ToolHookRecorder {
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl ToolHookRecorder {
+    fn record(&self, hook: &str, ctx: &RunContext, tool_name: &str) {
+        let id = ctx.tool_call_id().unwrap_or("none");
+        self.events
+            .lock()
+            .unwrap()
+            .push(format!("{} {} {}", hook, tool_name, id));
+    }
+}
+
+#[async_trait]
+impl AgentHooks for ToolHookRecorder {
+    async fn on_tool_start(
+        &self,
+        ctx: &RunContext,
+        _agent: &Agent,
+        tool_name: &str,
+        _arguments: &Value,
+    ) -> openai_agents::Result<()> {
+        self.record("start", ctx, tool_name);
+        Ok(())
+    }
+
+    async fn on_tool_end(
+        &self,
+        ctx: &RunContext,
+        _agent: &Agent,
+        tool_name: &str,
+        _result: &Value,
+    ) -> openai_agents::Result<()> {
+        self.record("end", ctx, tool_name);
+        Ok(())
+    }
+
+    async fn on_tool_error(
+        &self,
+        ctx: &RunContext,
+        _agent: &Agent,
+        tool_name: &str,
+        error: &AgentError,
+    ) -> openai_agents::Result<()> {
+        assert!(matches!(error, AgentError::ToolTimeout(_)));
+        self.record("error", ctx, tool_name);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_tool_timeout_reported_to_model_and_hooks() {
+    let recorder = ToolHookRecorder::default();
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[("call_1", "hang", json!({})), ("call_2", "fast", json!({}))]),
+        text("Gave up waiting"),
+    ]));
+    let agent = Agent::builder("Test")
+        .tool(hanging_tool().with_timeout(Duration::from_millis(20)))
+        .tool(FunctionTool::new_sync(
+            "fast",
+            "A quick tool",
+            json!({"type": "object", "properties": {}}),
+            |_args| Ok(json!("done")),
+        ))
+        .hook(recorder.clone())
+        .build();
+    let config = RunConfig {
+        model_override: Some(model.clone()),
+        tool_error_policy: ToolErrorPolicy::ReturnToModel,
+        ..Default::default()
+    };
+
+    let result = Runner::run_with_config(&agent, "Hi", config).await.unwrap();
+    assert_eq!(result.final_output(), "Gave up waiting");
+
+    // The calls run concurrently; their ids tell the hooks apart
+    let mut events = recorder.events.lock().unwrap().clone();
+    events.sort();
+    assert_eq!(
+        events,
+        vec![
+            "end fast call_2",
+            "end hang call_1",
+            "error hang call_1",
+            "start fast call_2",
+            "start hang call_1",
+        ]
+    );
+
+    let requests = model.requests();
+    let tool_message = requests[1]
+        .messages
+        .iter()
+        .find(|message| {
+            matches!(message, Message::Tool { tool_call_id, .. } if tool_call_id == "call_1")
+        })
+        .unwrap();
+    assert!(
+        tool_message
+            .content()
+            .unwrap()
+            .contains("timed out after 20ms")
+    );
+}
+
+#[tokio::test]
+async fn test_run_wide_tool_timeout_aborts_streaming_run() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[("call_1", "hang", json!({}))]),
+        text("unreachable"),
+    ]));
+    let agent = Agent::builder("Test").tool(hanging_tool()).build();
+    let config = RunConfig {
+        model_override: Some(model),
+        tool_timeout: Some(Duration::from_millis(20)),
+        ..Default::default()
+    };
+
+    let result = Runner::run_streamed_with_config(&agent, "Hi", config)
+        .await
+        .unwrap();
+    let err = result.final_result().await.unwrap_err();
+    assert!(matches!(err, AgentError::ToolTimeout(_)));
+}
+
+fn echo_tool() -> FunctionTool {
+    FunctionTool::new(
+        "echo",
+        "Echo the arguments back",
+        json!({"type": "object", "properties": {"text": {"type": "string"}}}),
+        |args| async move { Ok(args["text"].clone()) },
+    )
+}
+
+/// Check that the second request carries the tool call and its result
+fn assert_tool_transcript(requests: &[CompletionRequest]) {
+    let messages = &requests[1].messages;
+    let n = messages.len();
+
+    assert_eq!(
+        messages[n - 2],
+        Message::assistant_tool_calls(
+            None,
+            vec![ToolCall {
+                id: "call_7".to_string(),
+                name: "echo".to_string(),
+                arguments: json!({"text": "ping"}),
+            }],
+        )
+    );
+    assert_eq!(messages[n - 1], Message::tool("call_7", "\"ping\""));
+}
+
+#[tokio::test]
+async fn test_tool_call_transcript() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[("call_7", "echo", json!({"text": "ping"}))]),
+        text("pong"),
+    ]));
+    let agent = Agent::builder("Test").tool(echo_tool()).build();
+    let config = RunConfig {
+        model_override: Some(model.clone()),
+        ..Default::default()
+    };
+
+    let result = Runner::run_with_config(&agent, "Hi", config).await.unwrap();
+    assert_eq!(result.final_output(), "pong");
+    assert_tool_transcript(&model.requests());
+}
+
+#[tokio::test]
+async fn test_tool_call_transcript_streaming() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[("call_7", "echo", json!({"text": "ping"}))]),
+        text("pong"),
+    ]));
+    let agent = Agent::builder("Test").tool(echo_tool()).build();
+    let config = RunConfig {
+        model_override: Some(model.clone()),
+        ..Default::default()
+    };
+
+    let result = Runner::run_streamed_with_config(&agent, "Hi", config)
+        .await
+        .unwrap();
+    assert_eq!(result.final_output().await.unwrap(), "pong");
+    assert_tool_transcript(&model.requests());
+}
+
+#[test]
+fn test_message_serialization() {
+    let message = Message::assistant_tool_calls(
+        None,
+        vec![ToolCall {
+            id: "call_1".to_string(),
+            name: "echo".to_string(),
+            arguments: json!({"text": "hi"}),
+        }],
+    );
+    let value = serde_json::to_value(&message).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "role": "assistant",
+            "tool_calls": [{"id": "call_1", "name": "echo", "arguments": {"text": "hi"}}]
+        })
+    );
+    assert_eq!(serde_json::from_value::<Message>(value).unwrap(), message);
+
+    let value = serde_json::to_value(Message::tool("call_1", "hi")).unwrap();
+    assert_eq!(
+        value,
+        json!({"role": "tool", "tool_call_id": "call_1", "content": "hi"})
+    );
+}
+
+#[tokio::test]
+async fn test_streamed_handoff_events_and_session() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[("call_1", "transfer_to_billing", json!({}))]),
+        text("Billing here"),
+    ]));
+    let billing = Agent::builder("Billing")
+        .instructions("Handle billing.")
+        .build();
+    let triage = Agent::builder("Triage")
+        .instructions("Route requests.")
+        .handoff(Handoff::new(billing))
+        .build();
+    let session = Arc::new(InMemorySession::new());
+    let config = RunConfig {
+        model_override: Some(model.clone()),
+        session: Some(session.clone()),
+        ..Default::default()
+    };
+
+    let result = Runner::run_streamed_with_config(&triage, "Refund please", config)
+        .await
+        .unwrap();
+    let mut names = Vec::new();
+    let mut events = result.stream_events();
+    while let Some(event) = events.next().await {
+        match event {
+            StreamEvent::RunItem(item) => names.push(format!("{:?}", item.name)),
+            StreamEvent::AgentUpdated(update) => names.push(update.new_agent.name.clone()),
+            _ => {}
+        }
+    }
+    assert_eq!(
+        names,
+        vec![
+            "HandoffRequested",
+            "HandoffOccurred",
+            "Billing",
+            "MessageOutputCreated"
+        ]
+    );
+    assert_eq!(result.final_output().await.unwrap(), "Billing here");
+
+    // The new agent's instructions replace the system message
+    let requests = model.requests();
+    assert_eq!(requests[1].messages[0], Message::system("Handle billing."));
+
+    // The session holds the whole transcript, including the handoff
+    let saved: Vec<Message> = session
+        .get_items(None)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|item| serde_json::from_value(item).unwrap())
+        .collect();
+    let roles: Vec<_> = saved.iter().map(Message::role).collect();
+    assert_eq!(roles, vec!["user", "assistant", "tool", "assistant"]);
+    assert_eq!(saved, result.final_result().await.unwrap().to_input_list());
+}
+
+#[tokio::test]
+async fn test_session_resumes_tool_transcript() {
+    for streamed in [false, true] {
+        let model = Arc::new(ScriptedModel::new(vec![
+            tool_calls(&[("call_1", "echo", json!({"text": "hi"}))]),
+            text("Echoed"),
+            text("Again"),
+        ]));
+        let agent = Agent::builder("Test").tool(echo_tool()).build();
+        let session = Arc::new(InMemorySession::new());
+        let config = RunConfig {
+            model_override: Some(model.clone()),
+            session: Some(session.clone()),
+            ..Default::default()
+        };
+
+        for input in ["Echo hi", "Once more"] {
+            if streamed {
+                Runner::run_streamed_with_config(&agent, input, config.clone())
+                    .await
+                    .unwrap()
+                    .final_result()
+                    .await
+                    .unwrap();
+            } else {
+                Runner::run_with_config(&agent, input, config.clone())
+                    .await
+                    .unwrap();
+            }
+        }
+
+        // The second run sees the tool call and its result from the first
+        let messages = model.requests().pop().unwrap().messages;
+        assert_eq!(
+            messages,
+            vec![
+                Message::user("Echo hi"),
+                Message::assistant_tool_calls(
+                    None,
+                    vec![ToolCall {
+                        id: "call_1".to_string(),
+                        name: "echo".to_string(),
+                        arguments: json!({"text": "hi"}),
+                    }],
+                ),
+                Message::tool("call_1", "\"hi\""),
+                Message::assistant("Echoed"),
+                Message::user("Once more"),
+            ]
+        );
+        assert_eq!(session.get_items(None).await.unwrap().len(), 6);
+    }
+}
+
+#[tokio::test]
+async fn test_run_result_history() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[("call_1", "echo", json!({"text": "hi"}))]),
+        tool_calls(&[("call_2", "transfer_to_billing", json!({}))]),
+        text("Billing here"),
+    ]));
+    let billing = Agent::builder("Billing").build();
+    let triage = Agent::builder("Triage")
+        .instructions("Route requests.")
+        .tool(echo_tool())
+        .handoff(Handoff::new(billing))
+        .build();
+    let config = RunConfig {
+        model_override: Some(model),
+        ..Default::default()
+    };
+
+    let result = Runner::run_with_config(&triage, "Refund please", config)
+        .await
+        .unwrap();
+
+    let items: Vec<_> = result
+        .new_items()
+        .iter()
+        .map(|item| match item {
+            RunItem::MessageOutput { content } => format!("message {}", content),
+            RunItem::ToolCall { name, .. } => format!("call {}", name),
+            RunItem::ToolOutput { name, output } => format!("output {} {}", name, output),
+            RunItem::HandoffRequested { agent_name } => format!("requested {}", agent_name),
+            RunItem::HandoffOccurred { agent_name } => format!("handoff {}", agent_name),
+        })
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            "call echo",
+            "output echo \"hi\"",
+            "requested Billing",
+            "handoff Billing",
+            "message Billing here",
+        ]
+    );
+    assert_eq!(result.last_agent().unwrap().name, "Billing");
+    assert_eq!(result.raw_responses().len(), 3);
+
+    // The input list holds the conversation without the system message
+    let roles: Vec<_> = result.to_input_list().iter().map(Message::role).collect();
+    assert_eq!(
+        roles,
+        vec![
+            "user",
+            "assistant",
+            "tool",
+            "assistant",
+            "tool",
+            "assistant"
+        ]
+    );
+    assert_eq!(
+        result.to_input_list().last().unwrap(),
+        &Message::assistant("Billing here")
+    );
+}
+
+#[tokio::test]
+async fn test_continue_from_previous_result() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[("call_1", "transfer_to_billing", json!({}))]),
+        text("Billing here"),
+        text("Refund issued"),
+    ]));
+    let billing = Agent::builder("Billing")
+        .instructions("Handle billing.")
+        .build();
+    let triage = Agent::builder("Triage")
+        .instructions("Route requests.")
+        .handoff(Handoff::new(billing))
+        .build();
+    let config = RunConfig {
+        model_override: Some(model.clone()),
+        ..Default::default()
+    };
+
+    let first = Runner::run_with_config(&triage, "Refund please", config.clone())
+        .await
+        .unwrap();
+    let mut input = first.to_input_list();
+    input.push(Message::user("Order 42"));
+    let second = Runner::run_with_config(first.last_agent().unwrap(), input.clone(), config)
+        .await
+        .unwrap();
+    assert_eq!(second.final_output(), "Refund issued");
+    assert_eq!(second.last_agent().unwrap().name, "Billing");
+
+    // The billing agent picks up the whole conversation
+    let request = model.requests().pop().unwrap();
+    assert_eq!(request.messages[0], Message::system("Handle billing."));
+    assert_eq!(request.messages[1..], input[..]);
+
+    let mut expected = input;
+    expected.push(Message::assistant("Refund issued"));
+    assert_eq!(second.to_input_list(), expected);
+}
+
+#[tokio::test]
+async fn test_text_input_types() {
+    let text_input = String::from("Hi");
+    let model = Arc::new(ScriptedModel::new(vec![text("Hello"); 3]));
+    let agent = Agent::builder("Test").build();
+    let config = RunConfig {
+        model_override: Some(model.clone()),
+        ..Default::default()
+    };
+
+    // Every string type that converted into the input before it could be a
+    // message list still does
+    Runner::run_with_config(&agent, &text_input, config.clone())
+        .await
+        .unwrap();
+    Runner::run_with_config(&agent, Cow::from(&text_input[..]), config.clone())
+        .await
+        .unwrap();
+    Runner::run_with_config(&agent, text_input.into_boxed_str(), config)
+        .await
+        .unwrap();
+
+    for request in model.requests() {
+        assert_eq!(request.messages, vec![Message::user("Hi")]);
+    }
+}
+
+/// Records the usage reported for every model call
+struct UsageRecorder(Arc<Mutex<Vec<Option<Usage>>>>);
+
+#[async_trait]
+impl AgentHooks for UsageRecorder {
+    async fn on_llm_end(
+        &self,
+        _ctx: &RunContext,
+        _agent: &Agent,
+        response: &openai_agents::CompletionResponse,
+    ) -> openai_agents::Result<()> {
+        self.0.lock().unwrap().push(response.usage);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_usage_per_agent_and_run() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        with_usage(
+            tool_calls(&[("call_1", "transfer_to_billing", json!({}))]),
+            10,
+            5,
+        ),
+        with_usage(text("Billing here"), 20, 7),
+    ]));
+    let reported = Arc::new(Mutex::new(Vec::new()));
+    let billing = Agent::builder("Billing").build();
+    let triage = Agent::builder("Triage")
+        .handoff(Handoff::new(billing))
+        .hook(UsageRecorder(reported.clone()))
+        .build();
+    let config = RunConfig {
+        model_override: Some(model),
+        ..Default::default()
+    };
+
+    let streamed = Runner::run_streamed_with_config(&triage, "Refund please", config)
+        .await
+        .unwrap();
+    let result = streamed.final_result().await.unwrap();
+
+    let total = Usage {
+        requests: 2,
+        prompt_tokens: 30,
+        completion_tokens: 12,
+        total_tokens: 42,
+        ..Default::default()
+    };
+    assert_eq!(result.usage(), total);
+    assert_eq!(streamed.usage(), total);
+    assert_eq!(result.usage_by_agent()["Triage"].total_tokens, 15);
+    assert_eq!(result.usage_by_agent()["Billing"].total_tokens, 27);
+    assert_eq!(&streamed.usage_by_agent(), result.usage_by_agent());
+
+    // Hooks see the usage of the triage agent's own call
+    let reported = reported.lock().unwrap().clone();
+    assert_eq!(reported.len(), 1);
+    assert_eq!(reported[0].unwrap().prompt_tokens, 10);
+}
+
+struct FailingHook;
+
+#[async_trait]
+impl AgentHooks for FailingHook {
+    async fn on_start(&self, _ctx: &RunContext, _agent: &Agent) -> openai_agents::Result<()> {
+        Err(AgentError::ConfigError("hook failed".to_string()))
+    }
+}
+
+#[tokio::test]
+async fn test_hook_errors_abort_both_run_modes() {
+    for streamed in [false, true] {
+        let model = Arc::new(ScriptedModel::new(vec![text("unreachable")]));
+        let agent = Agent::builder("Test").hook(FailingHook).build();
+        let config = RunConfig {
+            model_override: Some(model.clone()),
+            ..Default::default()
+        };
+
+        let err = if streamed {
+            Runner::run_streamed_with_config(&agent, "Hi", config)
+                .await
+                .unwrap()
+                .final_result()
+                .await
+                .unwrap_err()
+        } else {
+            Runner::run_with_config(&agent, "Hi", config)
+                .await
+                .unwrap_err()
+        };
+        assert!(matches!(err, AgentError::ConfigError(ref m) if m == "hook failed"));
+        assert!(model.requests().is_empty());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_final_result_is_set_before_events_close() {
+    // The outcome must be stored before the event channel closes, or
+    // `final_result` can find neither a result nor an error. The runner
+    // orders the two, so a few hundred runs guard against a regression
+    let agent = Agent::builder("Test").build();
+    for _ in 0..500 {
+        let model = Arc::new(ScriptedModel::new(vec![text("Done")]));
+        let config = RunConfig {
+            model_override: Some(model),
+            ..Default::default()
+        };
+        let result = Runner::run_streamed_with_config(&agent, "Hi", config)
+            .await
+            .unwrap()
+            .final_result()
+            .await
+            .unwrap();
+        assert_eq!(result.final_output(), "Done");
+    }
+}
+
+#[tokio::test]
+async fn test_dropping_run_stops_it() {
+    let finished = Arc::new(AtomicUsize::new(0));
+    let tool = {
+        let finished = finished.clone();
+        FunctionTool::new(
+            "slow",
+            "A slow tool",
+            json!({"type": "object", "properties": {}}),
+            move |_args| {
+                let finished = finished.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    finished.fetch_add(1, Ordering::SeqCst);
+                    Ok(json!("done"))
+                }
+            },
+        )
+    };
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[("call_1", "slow", json!({}))]),
+        text("Done"),
+    ]));
+    let session = Arc::new(InMemorySession::new());
+    let agent = Agent::builder("Test").tool(tool).build();
+    let config = RunConfig {
+        model_override: Some(model.clone()),
+        session: Some(session.clone()),
+        ..Default::default()
+    };
+
+    let run = Runner::run_with_config(&agent, "Hi", config);
+    assert!(
+        tokio::time::timeout(Duration::from_millis(50), run)
+            .await
+            .is_err()
+    );
+
+    // Nothing happens once the caller gave up on the run
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(finished.load(Ordering::SeqCst), 0);
+    assert_eq!(model.requests().len(), 1);
+    assert!(session.get_items(None).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_handoff_keeps_system_messages_from_input() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        tool_calls(&[("call_1", "transfer_to_billing", json!({}))]),
+        text("Billing here"),
+    ]));
+    let billing = Agent::builder("Billing")
+        .instructions("Handle billing.")
+        .build();
+    let triage = Agent::builder("Triage")
+        .handoff(Handoff::new(billing))
+        .build();
+    let config = RunConfig {
+        model_override: Some(model.clone()),
+        ..Default::default()
+    };
+
+    // Without instructions on the starting agent, the leading system
+    // message is the caller's and must survive the handoff
+    let input = vec![
+        Message::system("Customer is on the premium plan."),
+        Message::user("Refund please"),
+    ];
+    let result = Runner::run_with_config(&triage, input, config)
+        .await
+        .unwrap();
+
+    let messages = model.requests().pop().unwrap().messages;
+    assert_eq!(messages[0], Message::system("Handle billing."));
+    assert_eq!(
+        messages[1],
+        Message::system("Customer is on the premium plan.")
+    );
+    assert_eq!(result.to_input_list()[0], messages[1]);
+}
@@ -0,0 +1,268 @@
+//! Integration tests for the built-in sessions
+
+mod common;
+
+use std::sync::Arc;
+
+use common::session::check_session;
+use common::{ScriptedModel, text};
+use openai_agents::models::ToolCall;
+use openai_agents::session::estimate_tokens;
+use openai_agents::{
+    Agent, AgentError, InMemorySession, Message, RunConfig, Runner, Session, SessionSettings,
+};
+use serde_json::{Value, json};
+
+#[tokio::test]
+async fn test_in_memory_session_conformance() {
+    check_session(&InMemorySession::new()).await;
+}
+
+fn tool_call(id: &str) -> Message {
+    Message::assistant_tool_calls(
+        None,
+        vec![ToolCall {
+            id: id.to_string(),
+            name: "lookup".to_string(),
+            arguments: json!({}),
+        }],
+    )
+}
+
+/// A conversation whose second exchange has a tool call with two results
+fn history() -> Vec<Value> {
+    [
+        Message::system("Be brief"),
+        Message::user("Find the order"),
+        Message::assistant("Which one?"),
+        Message::user("The last one"),
+        tool_call("call_1"),
+        Message::tool("call_1", "order 42"),
+        Message::tool("call_1", "shipped"),
+        Message::assistant("Order 42 has shipped"),
+    ]
+    .iter()
+    .map(|message| serde_json::to_value(message).unwrap())
+    .collect()
+}
+
+fn roles(items: &[Value]) -> Vec<&str> {
+    items
+        .iter()
+        .map(|item| item["role"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_trim_max_items() {
+    let trim = |max| SessionSettings::new().with_max_items(max).trim(history());
+
+    assert_eq!(trim(8), history());
+    assert_eq!(roles(&trim(1)), vec!["assistant"]);
+    assert_eq!(
+        roles(&trim(4)),
+        vec!["assistant", "tool", "tool", "assistant"]
+    );
+
+    // Three items would split the tool call from its results
+    assert_eq!(roles(&trim(3)), vec!["assistant"]);
+    assert!(trim(0).is_empty());
+}
+
+#[test]
+fn test_trim_max_tokens() {
+    let items = history();
+    let last_two: usize = items[6..].iter().map(estimate_tokens).sum();
+
+    let trimmed = SessionSettings::new()
+        .with_max_tokens(last_two)
+        .trim(items.clone());
+    assert_eq!(trimmed, items[7..]);
+
+    let all: usize = items.iter().map(estimate_tokens).sum();
+    let trimmed = SessionSettings::new()
+        .with_max_tokens(all)
+        .trim(items.clone());
+    assert_eq!(trimmed, items);
+}
+
+#[test]
+fn test_trim_keeps_initial_messages() {
+    let settings = SessionSettings::new()
+        .with_max_items(3)
+        .keep_initial_messages(true);
+    assert_eq!(settings.fetch_limit(), None);
+    assert_eq!(
+        roles(&settings.trim(history())),
+        vec!["system", "user", "assistant"]
+    );
+
+    let trimmed = settings.with_max_items(6).trim(history());
+    assert_eq!(trimmed[..2], history()[..2]);
+    assert_eq!(trimmed[2..], history()[4..]);
+}
+
+#[test]
+fn test_trim_drops_orphan_tool_results() {
+    // As loaded with a limit cutting through a tool exchange
+    let items = history()[5..].to_vec();
+    assert_eq!(
+        roles(&SessionSettings::new().trim(items)),
+        vec!["assistant"]
+    );
+}
+
+#[tokio::test]
+async fn test_runner_applies_session_settings() {
+    for override_settings in [false, true] {
+        let settings = SessionSettings::new().with_max_items(4);
+        let mut session = InMemorySession::new();
+        if !override_settings {
+            session = session.with_settings(settings.clone());
+        }
+        session.add_items(history()).await.unwrap();
+
+        let model = Arc::new(ScriptedModel::new(vec![text("Anything else?")]));
+        let agent = Agent::builder("Test").build();
+        let config = RunConfig {
+            model_override: Some(model.clone()),
+            session: Some(Arc::new(session)),
+            session_settings: override_settings.then_some(settings),
+            ..Default::default()
+        };
+        Runner::run_with_config(&agent, "Thanks", config)
+            .await
+            .unwrap();
+
+        let messages = model.requests().pop().unwrap().messages;
+        let roles: Vec<_> = messages.iter().map(Message::role).collect();
+        assert_eq!(
+            roles,
+            vec!["assistant", "tool", "tool", "assistant", "user"]
+        );
+    }
+}
+
+#[cfg(feature = "sqlite-session")]
+mod sqlite {
+    use std::path::PathBuf;
+
+    use openai_agents::SqliteSession;
+    use serde_json::json;
+
+    use super::*;
+
+    /// A database file in the temp directory, removed on drop
+    struct TempDb(PathBuf);
+
+    impl TempDb {
+        fn new() -> Self {
+            Self(std::env::temp_dir().join(format!("sessions-{}.db", uuid::Uuid::new_v4())))
+        }
+
+        fn path(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+    }
+
+    impl Drop for TempDb {
+        fn drop(&mut self) {
+            for suffix in ["", "-wal", "-shm"] {
+                let _ = std::fs::remove_file(format!("{}{}", self.path(), suffix));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_session_conformance() {
+        let db = TempDb::new();
+        let session = SqliteSession::new("user_1", db.path()).await.unwrap();
+        check_session(&session).await;
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_concurrent_sessions_on_one_file() {
+        let db = TempDb::new();
+        let mut sessions = Vec::new();
+        for _ in 0..4 {
+            sessions.push(SqliteSession::new("shared", db.path()).await.unwrap());
+        }
+
+        futures::future::try_join_all(
+            sessions
+                .iter()
+                .enumerate()
+                .map(|(i, session)| session.add_items((0..10).map(|j| json!([i, j])).collect())),
+        )
+        .await
+        .unwrap();
+
+        let items = sessions[0].get_items(None).await.unwrap();
+        assert_eq!(items.len(), 40);
+        for chunk in items.chunks(10) {
+            let writer = &chunk[0][0];
+            assert!(chunk.iter().all(|item| &item[0] == writer), "{:?}", items);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_table_name() {
+        let db = TempDb::new();
+        let default = SqliteSession::new("user_1", db.path()).await.unwrap();
+        let custom = SqliteSession::with_table("user_1", db.path(), "agent_history")
+            .await
+            .unwrap();
+        assert_eq!(custom.table(), "agent_history");
+
+        custom.add_items(vec![json!("custom")]).await.unwrap();
+        assert!(default.get_items(None).await.unwrap().is_empty());
+        assert_eq!(custom.get_items(None).await.unwrap(), vec![json!("custom")]);
+
+        for table in ["", "1st", "history; DROP TABLE sessions"] {
+            assert!(matches!(
+                SqliteSession::with_table("user_1", db.path(), table).await,
+                Err(AgentError::ConfigError(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_migrations() {
+        let db = TempDb::new();
+
+        // A database created before schema versioning is adopted as is
+        let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}?mode=rwc", db.path()))
+            .await
+            .unwrap();
+        sqlx::query(
+            "CREATE TABLE sessions (
+                session_id TEXT NOT NULL,
+                item_index INTEGER NOT NULL,
+                item_data TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (session_id, item_index)
+            )",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO sessions (session_id, item_index, item_data) VALUES (?, 0, ?)")
+            .bind("user_1")
+            .bind("\"legacy\"")
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+
+        let session = SqliteSession::new("user_1", db.path()).await.unwrap();
+        assert_eq!(session.schema_version().await.unwrap(), 1);
+        assert_eq!(
+            session.get_items(None).await.unwrap(),
+            vec![json!("legacy")]
+        );
+
+        // Reopening leaves the schema alone
+        let session = SqliteSession::new("user_1", db.path()).await.unwrap();
+        assert_eq!(session.schema_version().await.unwrap(), 1);
+    }
+}
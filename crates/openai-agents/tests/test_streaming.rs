@@ -5,7 +5,7 @@ use openai_agents::models::{
     CompletionRequest, CompletionResponse, CompletionStream, ModelProvider, StreamChunk,
     ToolCallDelta,
 };
-use openai_agents::{Agent, RunConfig, Runner, StreamEvent};
+use openai_agents::{Agent, RunConfig, RunContext, Runner, StreamEvent};
 use serde_json::Value;
 use std::sync::Arc;
 
@@ -20,6 +20,7 @@ impl ModelProvider for MockModel {
             content: Some("Mock response".to_string()),
             tool_calls: vec![],
             finish_reason: Some("stop".to_string()),
+            usage: None,
         })
     }
 
@@ -36,16 +37,19 @@ async fn test_runner_run_streamed_basic() {
             delta: Some("Hello".to_string()),
             tool_call_deltas: vec![],
             finish_reason: None,
+            usage: None,
         },
         StreamChunk {
             delta: Some(" world".to_string()),
             tool_call_deltas: vec![],
             finish_reason: None,
+            usage: None,
         },
         StreamChunk {
             delta: None,
             tool_call_deltas: vec![],
             finish_reason: Some("stop".to_string()),
+            usage: None,
         },
     ];
 
@@ -96,6 +100,7 @@ async fn test_runner_run_streamed_tool_call() {
                 arguments: Some("{\"location\":".to_string()),
             }],
             finish_reason: None,
+            usage: None,
         },
         StreamChunk {
             delta: None,
@@ -106,6 +111,7 @@ async fn test_runner_run_streamed_tool_call() {
                 arguments: Some("\"London\"}".to_string()),
             }],
             finish_reason: Some("tool_calls".to_string()),
+            usage: None,
         },
     ];
 
@@ -127,7 +133,7 @@ async fn test_runner_run_streamed_tool_call() {
         fn parameters_schema(&self) -> Value {
             json!({})
         }
-        async fn execute(&self, _args: Value) -> Result<Value> {
+        async fn execute(&self, _ctx: &RunContext, _args: Value) -> Result<Value> {
             Ok(json!({"temp": 20}))
         }
     }
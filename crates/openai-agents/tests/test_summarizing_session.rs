@@ -0,0 +1,297 @@
+//! Integration tests for the summarizing session
+
+mod common;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common::session::check_session;
+use common::{ScriptedModel, text};
+use openai_agents::error::Result;
+use openai_agents::models::ToolCall;
+use openai_agents::session::Compaction;
+use openai_agents::{
+    Agent, AgentError, InMemorySession, Message, RunConfig, Runner, Session, SummarizingSession,
+};
+use serde_json::{Value, json};
+
+fn item(message: Message) -> Value {
+    serde_json::to_value(message).unwrap()
+}
+
+fn turn(i: usize) -> Vec<Value> {
+    vec![
+        item(Message::user(format!("Question {}", i))),
+        item(Message::assistant(format!("Answer {}", i))),
+    ]
+}
+
+fn summarizing(model: &Arc<ScriptedModel>) -> SummarizingSession {
+    SummarizingSession::new(Arc::new(InMemorySession::new()), model.clone())
+        .with_model("summary-model")
+        .with_max_items(4)
+        .with_keep_recent(2)
+}
+
+#[tokio::test]
+async fn test_summarizing_session_conformance() {
+    // Large enough limits that the suite never triggers a compaction
+    let model = Arc::new(ScriptedModel::new(vec![]));
+    let session = summarizing(&model).with_max_items(100);
+    check_session(&session).await;
+    assert!(model.requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_compacts_older_items() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        text("The user asked two questions."),
+        text("The user asked four questions."),
+    ]));
+    let session = summarizing(&model);
+
+    session.add_items(turn(1)).await.unwrap();
+    session.add_items(turn(2)).await.unwrap();
+    assert!(model.requests().is_empty());
+
+    // The fifth item goes over the limit: all but the newest two are summarized
+    session.add_items(turn(3)).await.unwrap();
+    let items = session.get_items(None).await.unwrap();
+    assert_eq!(items.len(), 3);
+    assert_eq!(
+        serde_json::from_value::<Message>(items[0].clone()).unwrap(),
+        Message::user("Summary of the earlier conversation:\nThe user asked two questions.")
+    );
+    assert_eq!(items[1..], turn(3));
+    assert_eq!(
+        session
+            .compaction()
+            .await
+            .unwrap()
+            .unwrap()
+            .summarized_items,
+        4
+    );
+
+    let request = &model.requests()[0];
+    assert_eq!(request.model, "summary-model");
+    assert_eq!(
+        request.messages[1],
+        Message::user(
+            "user: Question 1\n\nassistant: Answer 1\n\nuser: Question 2\n\nassistant: Answer 2"
+        )
+    );
+
+    // The summary counts as one item, so the next write doesn't compact again
+    session
+        .add_items(vec![item(Message::user("Question 4"))])
+        .await
+        .unwrap();
+    assert_eq!(model.requests().len(), 1);
+
+    // Past the limit again, the earlier summary is folded into the new one
+    session
+        .add_items(vec![item(Message::assistant("Answer 4"))])
+        .await
+        .unwrap();
+    let items = session.get_items(None).await.unwrap();
+    assert_eq!(items.len(), 3);
+    assert_eq!(
+        session
+            .compaction()
+            .await
+            .unwrap()
+            .unwrap()
+            .summarized_items,
+        6
+    );
+    assert!(
+        model.requests()[1].messages[1]
+            .content()
+            .unwrap()
+            .starts_with("user: Summary of the earlier conversation:")
+    );
+}
+
+#[tokio::test]
+async fn test_compaction_keeps_tool_results_with_their_call() {
+    let model = Arc::new(ScriptedModel::new(vec![text("Summary")]));
+    let session = summarizing(&model);
+
+    let mut items = turn(1);
+    items.push(item(Message::assistant_tool_calls(
+        None,
+        vec![ToolCall {
+            id: "call_1".to_string(),
+            name: "lookup".to_string(),
+            arguments: json!({"order": 42}),
+        }],
+    )));
+    items.push(item(Message::tool("call_1", "shipped")));
+    items.push(item(Message::assistant("It shipped")));
+    session.add_items(items).await.unwrap();
+
+    // Keeping two items verbatim would split the call from its result
+    let items = session.get_items(None).await.unwrap();
+    let roles: Vec<_> = items.iter().map(|item| item["role"].clone()).collect();
+    assert_eq!(roles, vec![json!("user"), json!("assistant")]);
+    assert!(Compaction::from_item(&items[0]).is_some());
+    assert!(
+        model.requests()[0].messages[1]
+            .content()
+            .unwrap()
+            .contains("assistant: [called lookup({\"order\":42})]\n\ntool result: shipped")
+    );
+}
+
+#[tokio::test]
+async fn test_failed_summary_keeps_items() {
+    let model = Arc::new(ScriptedModel::new(vec![]));
+    let session = summarizing(&model);
+
+    for i in 0..3 {
+        session.add_items(turn(i)).await.unwrap();
+    }
+    assert_eq!(session.get_items(None).await.unwrap().len(), 6);
+    assert_eq!(session.compaction().await.unwrap(), None);
+    assert!(session.compact().await.is_err());
+}
+
+#[tokio::test]
+async fn test_compacts_on_token_limit() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        text("Long talk."),
+        text("Long talk, two short questions."),
+    ]));
+    let session = SummarizingSession::new(Arc::new(InMemorySession::new()), model.clone())
+        .with_max_items(100)
+        .with_max_tokens(40)
+        .with_keep_recent(2);
+
+    // The recent items alone exceed the limit, but aren't summarized
+    let long = "x".repeat(400);
+    session
+        .add_items(vec![
+            item(Message::user(long.clone())),
+            item(Message::assistant(long.clone())),
+        ])
+        .await
+        .unwrap();
+    assert!(model.requests().is_empty());
+
+    // Once older items exceed it, they are
+    session.add_items(turn(1)).await.unwrap();
+    let items = session.get_items(None).await.unwrap();
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[1..], turn(1));
+    assert_eq!(model.requests().len(), 1);
+
+    // The previous summary doesn't count, so it takes new items over the
+    // limit to summarize again rather than every write
+    session.add_items(turn(2)).await.unwrap();
+    assert_eq!(model.requests().len(), 1);
+    assert_eq!(session.get_items(None).await.unwrap().len(), 5);
+
+    session.add_items(turn(3)).await.unwrap();
+    assert_eq!(model.requests().len(), 2);
+    let items = session.get_items(None).await.unwrap();
+    assert_eq!(items[1..], turn(3));
+    assert_eq!(
+        Compaction::from_item(&items[0]).unwrap().summarized_items,
+        6
+    );
+}
+
+/// A session whose atomic replace always fails
+struct FailingReplace(InMemorySession);
+
+#[async_trait]
+impl Session for FailingReplace {
+    async fn get_items(&self, limit: Option<usize>) -> Result<Vec<Value>> {
+        self.0.get_items(limit).await
+    }
+
+    async fn add_items(&self, items: Vec<Value>) -> Result<()> {
+        self.0.add_items(items).await
+    }
+
+    async fn pop_item(&self) -> Result<Option<Value>> {
+        self.0.pop_item().await
+    }
+
+    async fn clear_session(&self) -> Result<()> {
+        self.0.clear_session().await
+    }
+
+    async fn replace_items(&self, _items: Vec<Value>) -> Result<()> {
+        Err(AgentError::SessionError("connection lost".to_string()))
+    }
+}
+
+#[tokio::test]
+async fn test_failed_rewrite_is_reported() {
+    let model = Arc::new(ScriptedModel::new(vec![text("Summary")]));
+    let inner = Arc::new(FailingReplace(InMemorySession::new()));
+    let session = SummarizingSession::new(inner.clone(), model)
+        .with_max_items(4)
+        .with_keep_recent(2);
+
+    session.add_items(turn(1)).await.unwrap();
+    session.add_items(turn(2)).await.unwrap();
+    assert!(matches!(
+        session.add_items(turn(3)).await,
+        Err(AgentError::SessionError(_))
+    ));
+    assert_eq!(inner.get_items(None).await.unwrap().len(), 6);
+}
+
+#[tokio::test]
+async fn test_run_with_summarizing_session() {
+    let model = Arc::new(ScriptedModel::new(vec![
+        text("Answer 1"),
+        text("Asked one question."),
+        text("Answer 2"),
+        text("Asked two questions."),
+    ]));
+    let session = Arc::new(
+        SummarizingSession::new(Arc::new(InMemorySession::new()), model.clone())
+            .with_max_items(1)
+            .with_keep_recent(0),
+    );
+    let agent = Agent::builder("Test").build();
+    let config = RunConfig {
+        model_override: Some(model.clone()),
+        session: Some(session.clone()),
+        ..Default::default()
+    };
+
+    Runner::run_with_config(&agent, "Question 1", config.clone())
+        .await
+        .unwrap();
+    Runner::run_with_config(&agent, "Question 2", config)
+        .await
+        .unwrap();
+
+    // The second run sees the summary in place of the first exchange
+    let requests = model.requests();
+    assert_eq!(requests.len(), 4);
+    assert_eq!(
+        requests[2].messages,
+        vec![
+            Message::user("Summary of the earlier conversation:\nAsked one question."),
+            Message::user("Question 2"),
+        ]
+    );
+
+    // And its exchange is folded into the next summary
+    let items = session.get_items(None).await.unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(
+        serde_json::from_value::<Message>(items[0].clone()).unwrap(),
+        Message::user("Summary of the earlier conversation:\nAsked two questions.")
+    );
+    assert_eq!(
+        Compaction::from_item(&items[0]).unwrap().summarized_items,
+        4
+    );
+}
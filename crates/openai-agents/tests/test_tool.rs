@@ -0,0 +1,69 @@
+//! Unit tests for the tool module
+
+use openai_agents::{AgentError, FunctionTool, RunContext, Tool};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Deserialize, JsonSchema)]
+struct AddArgs {
+    a: i64,
+    b: i64,
+}
+
+#[tokio::test]
+async fn test_function_tool_async_closure() {
+    let tool = FunctionTool::new(
+        "echo",
+        "Echo the arguments back",
+        json!({"type": "object", "properties": {}}),
+        |args| async move { Ok(json!({ "echo": args })) },
+    );
+
+    assert_eq!(tool.name(), "echo");
+    assert_eq!(tool.description(), "Echo the arguments back");
+
+    let result = tool
+        .execute(&RunContext::default(), json!({"x": 1}))
+        .await
+        .unwrap();
+    assert_eq!(result, json!({"echo": {"x": 1}}));
+}
+
+#[tokio::test]
+async fn test_function_tool_typed() {
+    let tool = FunctionTool::typed("add", "Add two numbers", |args: AddArgs| async move {
+        Ok(args.a + args.b)
+    });
+
+    let schema = tool.parameters_schema();
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["properties"]["a"]["type"], "integer");
+    assert_eq!(schema["required"], json!(["a", "b"]));
+    assert_eq!(schema["additionalProperties"], json!(false));
+
+    let result = tool
+        .execute(&RunContext::default(), json!({"a": 2, "b": 3}))
+        .await
+        .unwrap();
+    assert_eq!(result, json!(5));
+}
+
+#[tokio::test]
+async fn test_function_tool_typed_invalid_arguments() {
+    let tool =
+        FunctionTool::typed_sync(
+            "add",
+            "Add two numbers",
+            |args: AddArgs| Ok(args.a + args.b),
+        );
+
+    let err = tool
+        .execute(&RunContext::default(), json!({"a": "two"}))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AgentError::ToolExecutionFailed { ref tool_name, .. } if tool_name == "add"
+    ));
+}